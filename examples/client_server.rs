@@ -52,7 +52,7 @@ pub fn main() {
 }
 
 fn client_thread(port: u16) {
-    let mut sock = TcpStream::connect(&format!("127.0.0.1:{port}")).expect("Failed to connect");
+    let mut sock = TcpStream::connect(format!("127.0.0.1:{port}")).expect("Failed to connect");
 
     println!("[client] Sending Ping !");
     Message::Ping.to_writer(&mut sock).expect("[client] Failed to send Ping");
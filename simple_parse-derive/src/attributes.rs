@@ -11,13 +11,26 @@ use crate::*;
 #[darling(attributes(sp))]
 /// Attributes that can be use on the top level enum declaration
 pub(crate) struct EnumAttributes {
-    /// The type used to parse the variant id
+    /// The type used to parse the variant id : an integer type, or `[u8; N]` for byte string ids
     #[darling(default)]
     pub id_type: Option<String>,
 
     /// Specifies the default  endiannesss for the whole enum.
     #[darling(default)]
     pub endian: Option<String>,
+
+    /// Specifies the default `len_type` for every field of the enum's variants.
+    #[darling(default)]
+    pub len_type: Option<String>,
+
+    /// Order in which `#[sp(bits)]` fields are packed : "msb" (default) or "lsb"
+    #[darling(default)]
+    pub bit_order: Option<String>,
+
+    /// Bytes that are checked before the variant id when reading and emitted when writing.
+    /// See the `magic` of structs
+    #[darling(default)]
+    pub magic: Option<syn::Lit>,
 }
 
 /// Attributes that can be use on each enum variant.
@@ -25,10 +38,11 @@ pub(crate) struct EnumAttributes {
 #[derive(FromVariant)]
 #[darling(attributes(sp))]
 pub(crate) struct VariantAttributes {
-    /// The numerical representation of this variant.
+    /// The representation of this variant : an integer that can be negative with a signed `id_type`,
+    /// or a byte string such as a FourCC (e.g. `#[sp(id = b"IHDR")]`) which every variant must then use.
     /// When not specified, C style ids are used (First variant starts at 0, subsequent are [prev + 1])
     #[darling(default)]
-    pub id: Option<usize>,
+    pub id: Option<syn::Lit>,
 
     /// Specifies the default endiannesss for the whole Variant
     #[darling(default)]
@@ -45,6 +59,29 @@ pub(crate) struct StructAttributes {
     /// Specifies the default endiannesss for the whole struct
     #[darling(default)]
     pub endian: Option<String>,
+
+    /// Specifies the default `len_type` for every field of the struct
+    #[darling(default)]
+    pub len_type: Option<String>,
+
+    /// Order in which `#[sp(bits)]` fields are packed : "msb" (default) or "lsb"
+    /// - msb : The first field uses the most significant bits of the first byte
+    /// - lsb : The first field uses the least significant bits of the first byte
+    #[darling(default)]
+    pub bit_order: Option<String>,
+
+    /// Bytes that are checked before the first field when reading and emitted when writing.
+    /// They are not stored in the struct.
+    /// ```Rust
+    /// #[sp(magic = b"BM")]
+    /// struct BmpHeader {
+    ///     size: u32,
+    /// }
+    /// ```
+    /// The value can also be an integer with a type suffix (e.g. `0xCAFEBABE_u32`) which follows the `endian` of the struct.
+    /// Reading fails with `SpError::BadMagic` if the bytes do not match
+    #[darling(default)]
+    pub magic: Option<syn::Lit>,
 }
 
 #[derive(Default, Debug, PartialEq)]
@@ -61,9 +98,54 @@ pub(crate) struct FieldAttributes {
     ///     options: Vec<Options>,
     /// }
     /// ```
+    /// It can also be an expression over the fields declared before it, e.g. `len="(flags >> 4) + 1"`.
+    /// Integer arithmetic is checked and reading fails with `SpError::InvalidBytes` on overflow.
+    /// As nothing is written back into the fields of an expression, writing fails with `SpError::LenMismatch`
+    /// unless the expression matches the number of items. Use `calc` on these fields to keep them in sync.
     #[darling(default)]
     pub len: Option<String>,
 
+    /// Same as `len` but the referenced field holds the number of bytes taken by the items.
+    /// Items are read until exactly that many bytes are consumed.
+    /// e.g.
+    /// ```Rust
+    /// struct Chunk {
+    ///     options_size: u16,
+    ///     #[sp(byte_len="options_size")]
+    ///     options: Vec<TlvOption>,
+    /// }
+    /// ```
+    /// Supported on `Vec<T>`, `VecDeque<T>` and `LinkedList<T>`
+    #[darling(default)]
+    pub byte_len: Option<String>,
+
+    /// Computes the value of the field from the other fields when writing. The field is read as usual.
+    /// This is typically the counterpart of a `len` expression :
+    /// ```Rust
+    /// struct Packet {
+    ///     #[sp(calc="payload.len() + 4")]
+    ///     total_len: u32,
+    ///     #[sp(len="total_len - 4")]
+    ///     payload: Vec<u8>,
+    /// }
+    /// ```
+    /// The current value of the field is ignored. Integer arithmetic is checked and writing fails with
+    /// `SpError::CountFieldOverflow` if the result overflows or does not fit in the field.
+    #[darling(default)]
+    pub calc: Option<String>,
+
+    /// Integer type (`u8`, `u16`, `u32`, `u64` or `varint`) of the count prefixed to dynamically sized types.
+    /// This also applies to the nested types of the field, e.g. every count of a `Vec<String>`.
+    /// Defaults to `u32`.
+    /// ```Rust
+    /// struct Test {
+    ///     #[sp(len_type="u8")]
+    ///     name: String,
+    /// }
+    /// ```
+    #[darling(default)]
+    pub len_type: Option<String>,
+
     /// Allows for custom validation code to run directly after parsing or before writing a field.
     /// The provided value will be parsed as a comma seperated function name optionnaly followed by field names.
     /// When reading, any reference to fields __after__ the current field will be passed as `None` as their contents have not yet been parsed.
@@ -102,7 +184,7 @@ pub(crate) struct FieldAttributes {
     ///     }
     /// ```
     /// Will end up generating code that calls a function with signature :
-    ///     fn custom_parser(some_field: &bool, src: &mut Read, ctx: &mut SpCtx) -> Result<T, SpError>
+    ///     fn custom_parser(some_field: &bool, src: &mut SpReader, ctx: &mut SpCtx) -> Result<T, SpError>
     #[darling(default)]
     pub reader: Option<String>,
     
@@ -118,15 +200,189 @@ pub(crate) struct FieldAttributes {
     ///     }
     /// ```
     /// Will end up generating code that calls a function with signature :
-    ///     fn custom_writer(this: &Option<usize>, ctx: &mut SpCtx, dst: &mut Write) -> Result<usize, SpError>
+    ///     fn custom_writer(this: &Option<usize>, ctx: &mut SpCtx, dst: &mut SpWriter) -> Result<usize, SpError>
     #[darling(default)]
     pub writer: Option<String>,
 
+    /// Same as `reader` but used by `SpAsyncRead`. The function must be async :
+    ///     async fn custom_parser(some_field: &bool, src: &mut SpAsyncReader, ctx: &mut SpCtx) -> Result<T, SpError>
+    /// Fields annotated with `reader` must also provide an `async_reader` to derive `SpAsyncRead`.
+    #[darling(default)]
+    pub async_reader: Option<String>,
+
+    /// Same as `writer` but used by `SpAsyncWrite`. The function must be async :
+    ///     async fn custom_writer(this: &Option<usize>, ctx: &mut SpCtx, dst: &mut SpAsyncWriter) -> Result<usize, SpError>
+    /// Fields annotated with `writer` must also provide an `async_writer` to derive `SpAsyncWrite`.
+    #[darling(default)]
+    pub async_writer: Option<String>,
+
     /// Specifies the endiannes of the specific field. The data will
     /// be converted to the native endianness when necessary.
     #[darling(default)]
     pub endian: Option<String>,
 
+    /// Reads/writes an integer primitive as a LEB128 varint (zigzag encoded for signed types).
+    /// Values that do not fit in the field's type are rejected with `SpError::InvalidBytes`
+    #[darling(default)]
+    pub varint: Option<()>,
+
+    /// Reads/writes an integer or bool field using only N bits.
+    /// Consecutive bit fields are packed together following the struct's `bit_order`, the
+    /// bits of the last byte that are left over are ignored when reading and zeroed when writing.
+    /// ```Rust
+    /// struct Ipv4Header {
+    ///     #[sp(bits=4)]
+    ///     version: u8,
+    ///     #[sp(bits=4)]
+    ///     ihl: u8,
+    /// }
+    /// ```
+    /// Writing a value that does not fit in N bits fails with `SpError::BitFieldOverflow`
+    #[darling(default)]
+    pub bits: Option<u32>,
+
+    /// Stores a `String`, `CString` or `Vec<u8>` in exactly N bytes.
+    /// Trailing `pad` bytes are stripped when reading and the value is padded to N bytes when writing.
+    /// ```Rust
+    /// struct TarHeader {
+    ///     #[sp(fixed_len = 100)]
+    ///     name: String,
+    /// }
+    /// ```
+    /// Writing a value longer than N bytes fails with `SpError::FixedLenOverflow`
+    #[darling(default)]
+    pub fixed_len: Option<usize>,
+
+    /// The padding byte of a `fixed_len` field or of the `pad_before`, `pad_after` and `align` bytes. Defaults to 0
+    #[darling(default)]
+    pub pad: Option<u8>,
+
+    /// Number of padding bytes before the field. They are skipped when reading and filled with `pad` when writing.
+    /// ```Rust
+    /// struct BmpHeader {
+    ///     size: u32,
+    ///     #[sp(pad_before = 4)]
+    ///     pixel_array_offset: u32,
+    /// }
+    /// ```
+    #[darling(default)]
+    pub pad_before: Option<usize>,
+
+    /// Number of padding bytes after the field. See `pad_before`
+    #[darling(default)]
+    pub pad_after: Option<usize>,
+
+    /// Adds padding before the field (after any `pad_before`) until `SpCtx.cursor` is a multiple of N
+    #[darling(default)]
+    pub align: Option<usize>,
+
+    /// Moves to the position given by an expression over the fields declared before this one, then reads/writes the field.
+    /// This requires deriving `SpSeekRead`/`SpSeekWrite` instead of `SpRead`/`SpWrite`.
+    /// ```Rust
+    /// #[derive(SpSeekRead, SpSeekWrite)]
+    /// struct BmpFile {
+    ///     pixel_array_offset: u32,
+    ///     #[sp(offset = "pixel_array_offset", eof)]
+    ///     pixels: Vec<u8>,
+    /// }
+    /// ```
+    /// The following fields are read/written from the end of this field unless `restore` is set.
+    #[darling(default)]
+    pub offset: Option<String>,
+
+    /// What an `offset` is relative to :
+    /// - start (default) : Where reading/writing started (`SpCtx.cursor` 0)
+    /// - struct : The start of the struct/enum that contains the field (`SpCtx.struct_start`)
+    #[darling(default)]
+    pub offset_from: Option<String>,
+
+    /// Goes back to the previous position once the `offset` field was read/written
+    #[darling(default)]
+    pub restore: Option<()>,
+
+    /// Holds a checksum of the bytes of other fields. It is verified when reading and computed when writing,
+    /// ignoring the current value of the field :
+    /// ```Rust
+    /// struct Frame {
+    ///     kind: u8,
+    ///     payload: Vec<u8>,
+    ///     #[sp(checksum = "crc32", covers = "kind..=payload")]
+    ///     crc: u32,
+    /// }
+    /// ```
+    /// The algorithm is one of crc16, crc32, sum8 or the path to a type that implements `SpChecksum`.
+    /// Its output type must be the type of the field. Reading fails with `SpError::BadChecksum` on mismatch.
+    #[darling(default)]
+    pub checksum: Option<String>,
+
+    /// Fields declared before a `checksum` field whose bytes it covers, as `first..=last`, `first..end` or a single field.
+    /// Defaults to every field before the checksum.
+    #[darling(default)]
+    pub covers: Option<String>,
+
+    /// Fails with `SpError::BadPadding` when the padding of the field that is read does not only contain the `pad` byte
+    #[darling(default)]
+    pub strict_pad: Option<()>,
+
+    /// Reads a `Vec<u8>`, `String` or `CString` until the end of the input. Only allowed on the last field.
+    /// Nothing is prefixed to the bytes when writing.
+    /// ```Rust
+    /// struct Datagram {
+    ///     port: u16,
+    ///     #[sp(eof)]
+    ///     payload: Vec<u8>,
+    /// }
+    /// ```
+    #[darling(default)]
+    pub eof: Option<()>,
+
+    /// Reads items until the terminator is found. The terminator is written after the items.
+    /// For `String` and `CString`, the value is the text of the terminator. For `Vec<T>`, `VecDeque<T>` and
+    /// `LinkedList<T>`, it is an expression of the element that ends the list.
+    /// ```Rust
+    /// struct Request {
+    ///     #[sp(until="\r\n")]
+    ///     line: String,
+    ///     #[sp(until="0xFF")]
+    ///     ids: Vec<u8>,
+    /// }
+    /// ```
+    /// Writing a value that contains its terminator fails with `SpError::AmbiguousTerminator`
+    #[darling(default)]
+    pub until: Option<String>,
+
+    /// Only reads an `Option<T>` field when the expression over the fields declared before it is true.
+    /// No tag is read or written, the field is `None` when the condition is false.
+    /// ```Rust
+    /// struct Header {
+    ///     flags: u8,
+    ///     #[sp(cond="flags & 0x1 != 0")]
+    ///     checksum: Option<u32>,
+    /// }
+    /// ```
+    /// Writing fails with `SpError::CondMismatch` if the condition does not match whether the field is `Some`
+    #[darling(default)]
+    pub cond: Option<String>,
+
+    /// Checks that the field holds a constant when reading and always writes that constant.
+    /// The value is a byte string for `[u8; N]` fields or an integer that follows the endianness of the field.
+    /// ```Rust
+    /// struct Header {
+    ///     #[sp(const = 2)]
+    ///     version: u16,
+    ///     #[sp(const = b"\0\0")]
+    ///     reserved: [u8; 2],
+    /// }
+    /// ```
+    /// Reading fails with `SpError::BadMagic` if the bytes do not match. The current value of the field is ignored when writing
+    #[darling(default, rename = "const")]
+    pub constant: Option<syn::Lit>,
+
+    /// Keeps the terminator of an `until` field in its value.
+    /// It is only written once if the value already ends with it.
+    #[darling(default)]
+    pub keep_until: Option<()>,
+
     /// Specifies whether this field's type is variably sized
     /// 
     /// This should only be required when a custom type has a variable size.
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use darling::{FromDeriveInput, FromField, FromVariant};
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DataEnum, DeriveInput, Field, GenericParam, Generics};
+use syn::{parse_macro_input, parse_quote, DataEnum, DeriveInput, Field, GenericParam, Generics};
 
 mod attributes;
 mod read;
@@ -16,7 +16,18 @@ pub(crate) use attributes::*;
 /// For a list of valid `#[sp(X)]` attributes, consult [attributes.rs](https://github.com/elast0ny/simple_parse/tree/master/simple_parse-derive/src/attributes.rs)
 pub fn generate_read(input: TokenStream) -> TokenStream {
     let mut input = parse_macro_input!(input as DeriveInput);
-    let res = read::generate(&mut input);
+    let res = read::generate(&mut input, read::ReadSource::Reader);
+    proc_macro::TokenStream::from(res)
+}
+
+#[proc_macro_derive(SpReadRaw, attributes(sp))]
+/// Implements SpReadRaw on structs and enums
+///
+/// Lifetimes of the type are borrowed from the input bytes.
+/// For a list of valid `#[sp(X)]` attributes, consult [attributes.rs](https://github.com/elast0ny/simple_parse/tree/master/simple_parse-derive/src/attributes.rs)
+pub fn generate_read_raw(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    let res = read::generate(&mut input, read::ReadSource::Slice);
     proc_macro::TokenStream::from(res)
 }
 
@@ -26,7 +37,49 @@ pub fn generate_read(input: TokenStream) -> TokenStream {
 /// For a list of valid `#[sp(X)]` attributes, consult [attributes.rs](https://github.com/elast0ny/simple_parse/tree/master/simple_parse-derive/src/attributes.rs)
 pub fn generate_write(input: TokenStream) -> TokenStream {
     let mut input = parse_macro_input!(input as DeriveInput);
-    let res = write::generate(&mut input);
+    let res = write::generate(&mut input, write::WriteDest::Writer);
+    proc_macro::TokenStream::from(res)
+}
+
+#[proc_macro_derive(SpAsyncRead, attributes(sp))]
+/// Implements SpAsyncRead on structs and enums
+///
+/// For a list of valid `#[sp(X)]` attributes, consult [attributes.rs](https://github.com/elast0ny/simple_parse/tree/master/simple_parse-derive/src/attributes.rs)
+pub fn generate_async_read(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    let res = read::generate(&mut input, read::ReadSource::AsyncReader);
+    proc_macro::TokenStream::from(res)
+}
+
+#[proc_macro_derive(SpAsyncWrite, attributes(sp))]
+/// Implements SpAsyncWrite on structs and enums
+///
+/// For a list of valid `#[sp(X)]` attributes, consult [attributes.rs](https://github.com/elast0ny/simple_parse/tree/master/simple_parse-derive/src/attributes.rs)
+pub fn generate_async_write(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    let res = write::generate(&mut input, write::WriteDest::AsyncWriter);
+    proc_macro::TokenStream::from(res)
+}
+
+#[proc_macro_derive(SpSeekRead, attributes(sp))]
+/// Implements SpSeekRead on structs and enums
+///
+/// This is needed by types that contain `#[sp(offset)]` fields.
+/// For a list of valid `#[sp(X)]` attributes, consult [attributes.rs](https://github.com/elast0ny/simple_parse/tree/master/simple_parse-derive/src/attributes.rs)
+pub fn generate_seek_read(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    let res = read::generate(&mut input, read::ReadSource::SeekReader);
+    proc_macro::TokenStream::from(res)
+}
+
+#[proc_macro_derive(SpSeekWrite, attributes(sp))]
+/// Implements SpSeekWrite on structs and enums
+///
+/// This is needed by types that contain `#[sp(offset)]` fields.
+/// For a list of valid `#[sp(X)]` attributes, consult [attributes.rs](https://github.com/elast0ny/simple_parse/tree/master/simple_parse-derive/src/attributes.rs)
+pub fn generate_seek_write(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    let res = write::generate(&mut input, write::WriteDest::SeekWriter);
     proc_macro::TokenStream::from(res)
 }
 
@@ -83,6 +136,27 @@ pub(crate) fn generate_field_name(
     fname.parse().unwrap()
 }
 
+/// Generates a `.map_err()` that adds the field's location to an error
+pub(crate) fn field_error_context(
+    field: &Field,
+    idx: usize,
+    field_type: &syn::Type,
+    parent: &str,
+    variant: Option<&str>,
+) -> proc_macro2::TokenStream {
+    let mut label = match field.ident {
+        Some(ref i) => i.to_string(),
+        None => idx.to_string(),
+    };
+    if let Some(v) = variant {
+        label = format!("{}.{}", v, label);
+    }
+
+    quote! {
+        .map_err(|e: ::simple_parse::SpError| e.in_field(#parent, #label, stringify!(#field_type), ctx.cursor))
+    }
+}
+
 /// Returns whether the string is set to "little"
 pub(crate) fn is_lower_endian(val: &str) -> bool {
     if val == "little" {
@@ -94,62 +168,785 @@ pub(crate) fn is_lower_endian(val: &str) -> bool {
     }
 }
 
-/// Validates an enum variant's IDs and returns the smallest type that can fit the biggest variant id
-pub(crate) fn get_enum_id_type(data: &DataEnum, attrs: &EnumAttributes) -> syn::Type {
-    let mut seen_ids: HashMap<usize, String> = HashMap::new();
-    // Go through every field to find the biggest variant ID
-    let mut next_variant_id: usize = 0;
-    for variant in data.variants.iter() {
-        let var_attrs: darling::Result<VariantAttributes> = FromVariant::from_variant(&variant);
-        let variant_name = &variant.ident;
-        let variant_id = match var_attrs {
-            Ok(v) if v.id.is_some() => {
-                let specified_id = v.id.unwrap();
-                next_variant_id = specified_id + 1;
-                specified_id
+/// Returns the `SpLenType` variant for a `len_type` attribute
+pub(crate) fn len_type_variant(val: &str) -> proc_macro2::TokenStream {
+    match val {
+        "u8" => quote! {::simple_parse::SpLenType::U8},
+        "u16" => quote! {::simple_parse::SpLenType::U16},
+        "u32" => quote! {::simple_parse::SpLenType::U32},
+        "u64" => quote! {::simple_parse::SpLenType::U64},
+        "varint" => quote! {::simple_parse::SpLenType::Varint},
+        _ => panic!("Unknown len_type : {}. Valid values are u8, u16, u32, u64 and varint", val),
+    }
+}
+
+/// Returns the `SpBitOrder` variant for a `bit_order` attribute
+pub(crate) fn bit_order_variant(val: Option<&str>) -> proc_macro2::TokenStream {
+    match val {
+        None | Some("msb") => quote! {::simple_parse::SpBitOrder::Msb},
+        Some("lsb") => quote! {::simple_parse::SpBitOrder::Lsb},
+        Some(v) => panic!("Unknown bit_order : {}. Valid values are msb and lsb", v),
+    }
+}
+
+/// Makes sure a `#[sp(bits = N)]` field's type can hold N bits and returns N
+pub(crate) fn checked_bits(field_name: &str, ty: &syn::Type, attrs: &FieldAttributes) -> Option<u32> {
+    let num_bits = attrs.bits?;
+    if attrs.varint.is_some()
+        || attrs.reader.is_some()
+        || attrs.async_reader.is_some()
+        || attrs.writer.is_some()
+        || attrs.async_writer.is_some()
+    {
+        panic!("#[sp(bits)] on field '{}' cannot be combined with varint or a custom reader/writer", field_name);
+    }
+    let type_str = quote! {#ty}.to_string();
+    let max_bits = match type_str.as_str() {
+        "bool" => 1,
+        "u8" | "i8" => 8,
+        "u16" | "i16" => 16,
+        "u32" | "i32" => 32,
+        "u64" | "i64" | "usize" | "isize" => 64,
+        _ => panic!("#[sp(bits)] on field '{}' is only supported on integers and bool, not {}", field_name, type_str),
+    };
+    if num_bits == 0 || num_bits > max_bits {
+        panic!("#[sp(bits = {})] on field '{}' must be between 1 and {}", num_bits, field_name, max_bits);
+    }
+    Some(num_bits)
+}
+
+/// Makes sure a `#[sp(fixed_len = N)]` field has no conflicting attributes and returns N and the padding byte
+pub(crate) fn checked_fixed_len(field_name: &str, attrs: &FieldAttributes) -> Option<(usize, u8)> {
+    let num_bytes = match attrs.fixed_len {
+        Some(n) => n,
+        None if attrs.pad.is_some() && !has_padding(attrs) => {
+            panic!("#[sp(pad)] on field '{}' requires #[sp(fixed_len)], #[sp(pad_before)], #[sp(pad_after)] or #[sp(align)]", field_name)
+        }
+        None => return None,
+    };
+    if attrs.len.is_some()
+        || attrs.byte_len.is_some()
+        || attrs.len_type.is_some()
+        || attrs.varint.is_some()
+        || attrs.bits.is_some()
+        || attrs.reader.is_some()
+        || attrs.async_reader.is_some()
+        || attrs.writer.is_some()
+        || attrs.async_writer.is_some()
+    {
+        panic!("#[sp(fixed_len)] on field '{}' cannot be combined with len, byte_len, len_type, varint, bits or a custom reader/writer", field_name);
+    }
+    Some((num_bytes, attrs.pad.unwrap_or(0)))
+}
+
+/// Padding added around a field by `pad_before`, `align` and `pad_after`
+pub(crate) struct FieldPadding {
+    pub before: usize,
+    pub align: usize,
+    pub after: usize,
+    /// The padding byte
+    pub pad: u8,
+    /// Whether padding that is read must only contain `pad`
+    pub strict: bool,
+}
+
+impl FieldPadding {
+    /// Generates the number of bytes to add before the field
+    pub fn before_len(&self) -> Option<proc_macro2::TokenStream> {
+        let before = self.before;
+        let align = self.align;
+        if align > 1 {
+            Some(quote! {#before + ::simple_parse::align_padding(ctx.cursor + #before, #align)})
+        } else if before > 0 {
+            Some(quote! {#before})
+        } else {
+            None
+        }
+    }
+
+    /// Generates the number of bytes to add after the field
+    pub fn after_len(&self) -> Option<proc_macro2::TokenStream> {
+        let after = self.after;
+        if after > 0 {
+            Some(quote! {#after})
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns whether a field has any `pad_before`, `pad_after` or `align`
+fn has_padding(attrs: &FieldAttributes) -> bool {
+    attrs.pad_before.is_some() || attrs.pad_after.is_some() || attrs.align.is_some()
+}
+
+/// Makes sure the padding of a field is valid and returns it
+pub(crate) fn checked_padding(field_name: &str, attrs: &FieldAttributes) -> Option<FieldPadding> {
+    if !has_padding(attrs) {
+        if attrs.strict_pad.is_some() {
+            panic!("#[sp(strict_pad)] on field '{}' requires #[sp(pad_before)], #[sp(pad_after)] or #[sp(align)]", field_name);
+        }
+        return None;
+    }
+    if attrs.bits.is_some() {
+        panic!("#[sp(pad_before, pad_after, align)] on field '{}' cannot be combined with bits", field_name);
+    }
+    let align = attrs.align.unwrap_or(1);
+    if align == 0 {
+        panic!("#[sp(align)] on field '{}' must be greater than 0", field_name);
+    }
+    Some(FieldPadding {
+        before: attrs.pad_before.unwrap_or(0),
+        align,
+        after: attrs.pad_after.unwrap_or(0),
+        pad: attrs.pad.unwrap_or(0),
+        strict: attrs.strict_pad.is_some(),
+    })
+}
+
+/// Position of an `#[sp(offset)]` field
+pub(crate) struct FieldOffset<'a> {
+    /// Expression over the previous fields
+    pub expr: &'a str,
+    /// Whether the offset is relative to `SpCtx.struct_start`
+    pub from_struct: bool,
+    /// Whether the previous position is restored after the field
+    pub restore: bool,
+}
+
+/// Makes sure an `#[sp(offset)]` field is valid and returns its position. `seekable` tells whether the
+/// trait being derived can move around its input/output
+pub(crate) fn checked_offset<'a>(field_name: &str, attrs: &'a FieldAttributes, seekable: bool) -> Option<FieldOffset<'a>> {
+    let expr = match attrs.offset.as_deref() {
+        Some(e) => e,
+        None if attrs.offset_from.is_some() || attrs.restore.is_some() => {
+            panic!("#[sp(offset_from)] and #[sp(restore)] on field '{}' require #[sp(offset)]", field_name)
+        }
+        None => return None,
+    };
+    if !seekable {
+        panic!("#[sp(offset)] on field '{}' requires #[derive(SpSeekRead, SpSeekWrite)] instead of the other derives", field_name);
+    }
+    if attrs.bits.is_some() {
+        panic!("#[sp(offset)] on field '{}' cannot be combined with bits", field_name);
+    }
+    let from_struct = match attrs.offset_from.as_deref() {
+        None | Some("start") => false,
+        Some("struct") => true,
+        Some(v) => panic!("Unknown offset_from : {}. Valid values are start and struct", v),
+    };
+    Some(FieldOffset {
+        expr,
+        from_struct,
+        restore: attrs.restore.is_some(),
+    })
+}
+
+/// A `#[sp(checksum)]` field and the fields it covers
+pub(crate) struct FieldChecksum {
+    /// Index of the checksum field
+    pub idx: usize,
+    /// Type that implements `SpChecksum`
+    pub algo: syn::Type,
+    /// Index of the first covered field
+    pub start: usize,
+    /// Index of the last covered field
+    pub end: usize,
+}
+
+impl FieldChecksum {
+    /// Local variable holding the tapped reader/writer (or the start of a slice)
+    pub fn tap_ident(&self) -> syn::Ident {
+        quote::format_ident!("__sp_tap_{}", self.idx)
+    }
+
+    /// Local variable holding the computed checksum
+    pub fn sum_ident(&self) -> syn::Ident {
+        quote::format_ident!("__sp_sum_{}", self.idx)
+    }
+
+    /// Taps are opened in this order and closed in the reverse order
+    fn order(&self) -> (usize, std::cmp::Reverse<usize>, usize) {
+        (self.start, std::cmp::Reverse(self.end), self.idx)
+    }
+}
+
+/// Collects the `#[sp(checksum)]` fields and makes sure that the fields they cover can be tapped
+pub(crate) fn checked_checksums(fields: &[&syn::Field], field_names: &[String]) -> Vec<FieldChecksum> {
+    let mut checksums: Vec<FieldChecksum> = Vec::new();
+    let mut is_bits = Vec::with_capacity(fields.len());
+
+    for (idx, field) in fields.iter().enumerate() {
+        let attrs: FieldAttributes = FromField::from_field(field).unwrap();
+        is_bits.push(attrs.bits.is_some());
+        let field_name = &field_names[idx];
+        let algo = match attrs.checksum.as_deref() {
+            Some(a) => a,
+            None if attrs.covers.is_some() => panic!("#[sp(covers)] on field '{}' requires #[sp(checksum)]", field_name),
+            None => continue,
+        };
+        if attrs.len.is_some()
+            || attrs.byte_len.is_some()
+            || attrs.fixed_len.is_some()
+            || attrs.eof.is_some()
+            || attrs.until.is_some()
+            || attrs.cond.is_some()
+            || attrs.calc.is_some()
+            || attrs.constant.is_some()
+            || attrs.varint.is_some()
+            || attrs.bits.is_some()
+            || attrs.reader.is_some()
+            || attrs.async_reader.is_some()
+            || attrs.writer.is_some()
+            || attrs.async_writer.is_some()
+        {
+            panic!("#[sp(checksum)] on field '{}' cannot be combined with len, byte_len, fixed_len, eof, until, cond, calc, const, varint, bits or a custom reader/writer", field_name);
+        }
+        let algo: syn::Type = match algo {
+            "crc16" => parse_quote! {::simple_parse::Crc16},
+            "crc32" => parse_quote! {::simple_parse::Crc32},
+            "sum8" => parse_quote! {::simple_parse::Sum8},
+            path => match syn::parse_str(path) {
+                Ok(t) => t,
+                Err(_) => panic!("Unknown checksum '{}' on field '{}'. Valid values are crc16, crc32, sum8 or the path to a type implementing SpChecksum", path, field_name),
+            },
+        };
+
+        // Resolve the covered fields
+        let find_field = |name: &str| -> usize {
+            let name = name.trim();
+            match field_names[..idx].iter().position(|n| n == name) {
+                Some(i) => i,
+                None => panic!("#[sp(covers)] on field '{}' refers to an unknown field '{}'. Valid values are {:?}", field_name, name, &field_names[..idx]),
             }
-            _ => {
-                let cur = next_variant_id;
-                next_variant_id += 1;
-                cur
+        };
+        let (start, end) = match attrs.covers.as_deref() {
+            None if idx == 0 => panic!("#[sp(checksum)] on field '{}' has no fields before it to cover", field_name),
+            None => (0, idx - 1),
+            Some(c) => {
+                if let Some((first, last)) = c.split_once("..=") {
+                    (find_field(first), find_field(last))
+                } else if let Some((first, end)) = c.split_once("..") {
+                    let end = find_field(end);
+                    if end == 0 {
+                        panic!("#[sp(covers = \"{}\")] on field '{}' does not cover any field", c, field_name);
+                    }
+                    (find_field(first), end - 1)
+                } else {
+                    let i = find_field(c);
+                    (i, i)
+                }
             }
         };
+        if start > end {
+            panic!("#[sp(covers)] on field '{}' does not cover any field", field_name);
+        }
 
-        if let Some(v) = seen_ids.insert(variant_id, variant_name.to_string()) {
-            panic!(
-                "Field {} has the same ID as {} : {}",
-                variant_name.to_string(),
-                v,
-                variant_id
-            );
+        checksums.push(FieldChecksum { idx, algo, start, end });
+    }
+
+    for (i, c) in checksums.iter().enumerate() {
+        // Bytes of a group of bit fields are shared between its fields
+        if (c.start > 0 && is_bits[c.start] && is_bits[c.start - 1]) || (is_bits[c.end] && is_bits.get(c.end + 1) == Some(&true)) {
+            panic!("#[sp(covers)] on field '{}' cannot start or end in the middle of a group of bit fields", &field_names[c.idx]);
+        }
+        // Taps can only be nested
+        for o in checksums[i + 1..].iter() {
+            let disjoint = c.end < o.start || o.end < c.start;
+            let nested = (c.start <= o.start && o.end <= c.end) || (o.start <= c.start && c.end <= o.end);
+            if !disjoint && !nested {
+                panic!("The fields covered by the checksums '{}' and '{}' partially overlap", &field_names[c.idx], &field_names[o.idx]);
+            }
         }
     }
 
-    if next_variant_id != 0 {
-        next_variant_id -= 1;
+    checksums
+}
+
+/// Returns the checksums whose covered fields start at `idx` in the order their taps are opened
+pub(crate) fn checksums_opened_at(checksums: &[FieldChecksum], idx: usize) -> Vec<&FieldChecksum> {
+    let mut opened: Vec<&FieldChecksum> = checksums.iter().filter(|c| c.start == idx).collect();
+    opened.sort_by_key(|c| c.order());
+    opened
+}
+
+/// Returns the checksums whose covered fields end at `idx` in the order their taps are closed
+pub(crate) fn checksums_closed_at(checksums: &[FieldChecksum], idx: usize) -> Vec<&FieldChecksum> {
+    let mut closed: Vec<&FieldChecksum> = checksums.iter().filter(|c| c.end == idx).collect();
+    closed.sort_by_key(|c| std::cmp::Reverse(c.order()));
+    closed
+}
+
+/// Makes sure a `#[sp(eof)]` field is the last one and has no conflicting attributes
+pub(crate) fn checked_eof(field_name: &str, attrs: &FieldAttributes, is_last: bool) -> bool {
+    if attrs.eof.is_none() {
+        return false;
     }
-    let id_type: syn::Type = syn::parse_str(match attrs.id_type {
-        Some(ref s) => s.as_str(),
-        None => smallest_type_for_num(next_variant_id),
-    })
-    .unwrap();
+    if !is_last {
+        panic!("#[sp(eof)] on field '{}' can only be used on the last field", field_name);
+    }
+    if attrs.len.is_some()
+        || attrs.byte_len.is_some()
+        || attrs.fixed_len.is_some()
+        || attrs.len_type.is_some()
+        || attrs.varint.is_some()
+        || attrs.bits.is_some()
+        || attrs.reader.is_some()
+        || attrs.async_reader.is_some()
+        || attrs.writer.is_some()
+        || attrs.async_writer.is_some()
+    {
+        panic!("#[sp(eof)] on field '{}' cannot be combined with len, byte_len, fixed_len, len_type, varint, bits or a custom reader/writer", field_name);
+    }
+    true
+}
+
+/// Makes sure a `#[sp(until)]` field has no conflicting attributes and returns its terminator and whether it is kept
+pub(crate) fn checked_until<'a>(field_name: &str, attrs: &'a FieldAttributes) -> Option<(&'a str, bool)> {
+    let term = match attrs.until.as_deref() {
+        Some(t) => t,
+        None if attrs.keep_until.is_some() => panic!("#[sp(keep_until)] on field '{}' requires #[sp(until)]", field_name),
+        None => return None,
+    };
+    if term.is_empty() {
+        panic!("#[sp(until)] on field '{}' cannot be empty", field_name);
+    }
+    if attrs.len.is_some()
+        || attrs.byte_len.is_some()
+        || attrs.fixed_len.is_some()
+        || attrs.eof.is_some()
+        || attrs.calc.is_some()
+        || attrs.varint.is_some()
+        || attrs.bits.is_some()
+        || attrs.reader.is_some()
+        || attrs.async_reader.is_some()
+        || attrs.writer.is_some()
+        || attrs.async_writer.is_some()
+    {
+        panic!("#[sp(until)] on field '{}' cannot be combined with len, byte_len, fixed_len, eof, calc, varint, bits or a custom reader/writer", field_name);
+    }
+    Some((term, attrs.keep_until.is_some()))
+}
+
+/// Returns whether the `until` terminator of a type is a string instead of an element
+pub(crate) fn is_text_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(p) => match p.path.segments.last() {
+            Some(s) => s.ident == "String" || s.ident == "CString",
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Parses the element that ends the items of an `until` field
+pub(crate) fn until_elem(field_name: &str, term: &str) -> syn::Expr {
+    match syn::parse_str(term) {
+        Ok(v) => v,
+        Err(e) => panic!("#[sp(until)] on field '{}' is not a valid expression '{}' : {}", field_name, term, e),
+    }
+}
+
+/// Makes sure a `#[sp(cond)]` field is an `Option<T>` with no conflicting attributes and returns its condition and `T`
+pub(crate) fn checked_cond<'a>(field_name: &str, ty: &syn::Type, attrs: &'a FieldAttributes) -> Option<(&'a str, syn::Type)> {
+    let cond = attrs.cond.as_deref()?;
+    if attrs.len.is_some()
+        || attrs.byte_len.is_some()
+        || attrs.fixed_len.is_some()
+        || attrs.eof.is_some()
+        || attrs.until.is_some()
+        || attrs.calc.is_some()
+        || attrs.varint.is_some()
+        || attrs.bits.is_some()
+        || attrs.reader.is_some()
+        || attrs.async_reader.is_some()
+        || attrs.writer.is_some()
+        || attrs.async_writer.is_some()
+    {
+        panic!("#[sp(cond)] on field '{}' cannot be combined with len, byte_len, fixed_len, eof, until, calc, varint, bits or a custom reader/writer", field_name);
+    }
+    // Get the T of Option<T>
+    if let syn::Type::Path(p) = ty {
+        if let Some(s) = p.path.segments.last() {
+            if let syn::PathArguments::AngleBracketed(ref args) = s.arguments {
+                if let (true, Some(syn::GenericArgument::Type(inner))) = (s.ident == "Option", args.args.first()) {
+                    return Some((cond, inner.clone()));
+                }
+            }
+        }
+    }
+    panic!("#[sp(cond)] on field '{}' is only supported on Option<T>", field_name);
+}
+
+/// Makes sure a `#[sp(const)]` field has no conflicting attributes and returns its value
+pub(crate) fn checked_const<'a>(field_name: &str, attrs: &'a FieldAttributes) -> Option<&'a syn::Lit> {
+    let lit = attrs.constant.as_ref()?;
+    if attrs.len.is_some()
+        || attrs.byte_len.is_some()
+        || attrs.fixed_len.is_some()
+        || attrs.eof.is_some()
+        || attrs.until.is_some()
+        || attrs.cond.is_some()
+        || attrs.calc.is_some()
+        || attrs.varint.is_some()
+        || attrs.bits.is_some()
+        || attrs.reader.is_some()
+        || attrs.async_reader.is_some()
+        || attrs.writer.is_some()
+        || attrs.async_writer.is_some()
+    {
+        panic!("#[sp(const)] on field '{}' cannot be combined with len, byte_len, fixed_len, eof, until, cond, calc, varint, bits or a custom reader/writer", field_name);
+    }
+    Some(lit)
+}
+
+/// Converts the value of a `magic` or `const` into the bytes it stands for.
+/// Integers take their width from their suffix or from `ty` and are laid out following `is_le`
+pub(crate) fn literal_bytes(attr_name: &str, owner: &str, lit: &syn::Lit, ty: Option<&syn::Type>, is_le: bool) -> syn::LitByteStr {
+    let bytes = match lit {
+        syn::Lit::ByteStr(s) => s.value(),
+        syn::Lit::Int(i) => {
+            let type_str = match (i.suffix(), ty) {
+                ("", Some(ty)) => quote! {#ty}.to_string(),
+                ("", None) => panic!("#[sp({})] on '{}' needs an integer type suffix (e.g. {}u32)", attr_name, owner, i.base10_digits()),
+                (suffix, _) => suffix.to_string(),
+            };
+            let (num_bytes, is_signed) = match type_str.as_str() {
+                "u8" => (1, false),
+                "i8" => (1, true),
+                "u16" => (2, false),
+                "i16" => (2, true),
+                "u32" => (4, false),
+                "i32" => (4, true),
+                "u64" => (8, false),
+                "i64" => (8, true),
+                _ => panic!("#[sp({})] on '{}' only supports integers of type u8 to u64 and i8 to i64, not {}", attr_name, owner, type_str),
+            };
+            let v: u64 = match i.base10_parse() {
+                Ok(v) => v,
+                Err(e) => panic!("#[sp({})] on '{}' is not a valid integer : {}", attr_name, owner, e),
+            };
+            let max = u64::MAX >> (64 - num_bytes * 8 + is_signed as usize);
+            if v > max {
+                panic!("#[sp({})] on '{}' does not fit in {}", attr_name, owner, type_str);
+            }
+            let mut bytes = v.to_le_bytes()[..num_bytes].to_vec();
+            if !is_le {
+                bytes.reverse();
+            }
+            bytes
+        }
+        _ => panic!("#[sp({})] on '{}' must be a byte string (e.g. b\"BM\") or an integer", attr_name, owner),
+    };
+    if bytes.is_empty() {
+        panic!("#[sp({})] on '{}' cannot be empty", attr_name, owner);
+    }
+    syn::LitByteStr::new(&bytes, proc_macro2::Span::call_site())
+}
+
+/// Returns the bytes of a struct or enum's `magic`. Integers default to little endian
+pub(crate) fn magic_attr(owner: &str, magic: Option<&syn::Lit>, endian: Option<&str>) -> Option<syn::LitByteStr> {
+    let is_le = endian.map(is_lower_endian).unwrap_or(true);
+    magic.map(|lit| literal_bytes("magic", owner, lit, None, is_le))
+}
+
+/// Returns the field referenced by `len` or `byte_len` and whether it holds a number of bytes
+pub(crate) fn len_attr<'a>(field_name: &str, attrs: &'a FieldAttributes) -> Option<(&'a str, bool)> {
+    match (attrs.len.as_deref(), attrs.byte_len.as_deref()) {
+        (Some(_), Some(_)) => panic!("Field '{}' cannot have both #[sp(len)] and #[sp(byte_len)]", field_name),
+        (Some(v), None) => Some((v, false)),
+        (None, Some(v)) => Some((v, true)),
+        (None, None) => None,
+    }
+}
 
-    id_type
+/// Returns whether a `len` or `byte_len` value is the name of a field rather than an expression
+pub(crate) fn is_field_ref(val: &str) -> bool {
+    syn::parse_str::<syn::Ident>(val).is_ok()
 }
 
-// Returns the small unsigned integer type for a given usize value
-pub(crate) fn smallest_type_for_num(num: usize) -> &'static str {
-    if num <= u8::MAX as _ {
-        "u8"
-    } else if num <= u16::MAX as _ {
-        "u16"
-    } else if num <= u32::MAX as _ {
-        "u32"
-    } else if num <= u64::MAX as _ {
-        "u64"
+/// Parses the expression of a `len`, `byte_len` or `calc` attribute on the field `field_name`
+///
+/// Returns the expression with its integer arithmetic made checked along with the indexes of the fields it uses.
+/// Only the fields before `max_idx` can be used.
+pub(crate) fn parse_field_expr(attr_name: &str, field_name: &str, val: &str, field_names: &[String], max_idx: usize) -> (syn::Expr, Vec<usize>) {
+    let expr: syn::Expr = match syn::parse_str(val) {
+        Ok(v) => v,
+        Err(e) => panic!("#[sp({})] on field '{}' is not a valid expression '{}' : {}", attr_name, field_name, val, e),
+    };
+
+    let mut idents = Vec::new();
+    expr_idents(quote! {#expr}, &mut idents);
+
+    let mut used = Vec::new();
+    for (idx, name) in field_names.iter().enumerate() {
+        if !idents.contains(name) {
+            continue;
+        }
+        if idx >= max_idx {
+            panic!("#[sp({})] on field '{}' can only use fields declared before it. Valid values are {:?}", attr_name, field_name, &field_names[..max_idx]);
+        }
+        used.push(idx);
+    }
+
+    (checked_expr(&expr), used)
+}
+
+/// Collects the identifiers of an expression that can refer to a field
+fn expr_idents(tokens: proc_macro2::TokenStream, idents: &mut Vec<String>) {
+    use proc_macro2::TokenTree;
+    let mut prev_punct = None;
+    for tt in tokens {
+        match tt {
+            TokenTree::Group(ref g) => expr_idents(g.stream(), idents),
+            // Skip method names, struct fields and paths
+            TokenTree::Ident(ref i) if !matches!(prev_punct, Some('.') | Some(':')) => idents.push(i.to_string()),
+            _ => {}
+        }
+        prev_punct = match tt {
+            TokenTree::Punct(ref p) => Some(p.as_char()),
+            _ => None,
+        };
+    }
+}
+
+/// Replaces the integer arithmetic of an expression with calls to `SpCheckedOps`
+/// that return `None` from the enclosing closure on overflow
+fn checked_expr(expr: &syn::Expr) -> syn::Expr {
+    use syn::{BinOp, Expr, UnOp};
+    match expr {
+        Expr::Binary(b) => {
+            let left = checked_expr(&b.left);
+            let right = checked_expr(&b.right);
+            let op = match b.op {
+                BinOp::Add(_) => quote! {checked_add},
+                BinOp::Sub(_) => quote! {checked_sub},
+                BinOp::Mul(_) => quote! {checked_mul},
+                BinOp::Div(_) => quote! {checked_div},
+                BinOp::Rem(_) => quote! {checked_rem},
+                BinOp::Shl(_) => return parse_quote! {::simple_parse::SpCheckedOps::checked_shl(#left, (#right) as u32)?},
+                BinOp::Shr(_) => return parse_quote! {::simple_parse::SpCheckedOps::checked_shr(#left, (#right) as u32)?},
+                _ => {
+                    let mut b = b.clone();
+                    b.left = Box::new(left);
+                    b.right = Box::new(right);
+                    return Expr::Binary(b);
+                }
+            };
+            parse_quote! {::simple_parse::SpCheckedOps::#op(#left, #right)?}
+        }
+        Expr::Unary(u) => {
+            let inner = checked_expr(&u.expr);
+            if let UnOp::Neg(_) = u.op {
+                return parse_quote! {::simple_parse::SpCheckedOps::checked_neg(#inner)?};
+            }
+            let mut u = u.clone();
+            u.expr = Box::new(inner);
+            Expr::Unary(u)
+        }
+        Expr::Paren(p) => {
+            let mut p = p.clone();
+            p.expr = Box::new(checked_expr(&p.expr));
+            Expr::Paren(p)
+        }
+        Expr::Cast(c) => {
+            let mut c = c.clone();
+            c.expr = Box::new(checked_expr(&c.expr));
+            Expr::Cast(c)
+        }
+        Expr::MethodCall(m) => {
+            let mut m = m.clone();
+            m.receiver = Box::new(checked_expr(&m.receiver));
+            m.args = m.args.iter().map(checked_expr).collect();
+            Expr::MethodCall(m)
+        }
+        Expr::Call(c) => {
+            let mut c = c.clone();
+            c.args = c.args.iter().map(checked_expr).collect();
+            Expr::Call(c)
+        }
+        Expr::Index(i) => {
+            let mut i = i.clone();
+            i.expr = Box::new(checked_expr(&i.expr));
+            i.index = Box::new(checked_expr(&i.index));
+            Expr::Index(i)
+        }
+        _ => expr.clone(),
+    }
+}
+
+/// Declares a field used by an expression. Numbers are copied while other types are borrowed
+///
+/// `value_ref` must evaluate to a reference to the field's value
+pub(crate) fn expr_binding(name: &proc_macro2::TokenStream, ty: &syn::Type, value_ref: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let type_str = quote! {#ty}.to_string();
+    match type_str.as_str() {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "bool" | "char" => {
+            quote! {let #name: #ty = <#ty as ::core::clone::Clone>::clone(#value_ref);}
+        }
+        _ => quote! {let #name: &#ty = #value_ref;},
+    }
+}
+
+/// Generates the code that evaluates an expression as `Option<ty>` with the fields declared by `bindings`
+///
+/// The value is `None` if the arithmetic overflows or if the result does not fit in `ty`
+pub(crate) fn expr_eval(expr: &syn::Expr, bindings: &proc_macro2::TokenStream, ty: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        (|| -> ::core::option::Option<#ty> {
+            #bindings
+            ::core::convert::TryInto::<#ty>::try_into(#expr).ok()
+        })()
+    }
+}
+
+/// Id of an enum variant
+pub(crate) enum VariantId {
+    /// Integer id, possibly negative
+    Int(i128),
+    /// Byte string id such as a FourCC (e.g. `b"IHDR"`)
+    Bytes(Vec<u8>),
+}
+
+impl VariantId {
+    /// Generates the id as a value or a pattern of the enum's id type
+    pub fn tokens(&self) -> proc_macro2::TokenStream {
+        match self {
+            VariantId::Int(v) if *v < 0 => {
+                let abs = proc_macro2::Literal::u128_unsuffixed(v.unsigned_abs());
+                quote! {-#abs}
+            }
+            VariantId::Int(v) => {
+                let v = proc_macro2::Literal::u128_unsuffixed(*v as u128);
+                quote! {#v}
+            }
+            VariantId::Bytes(b) => {
+                let b = b.iter().map(|v| proc_macro2::Literal::u8_unsuffixed(*v));
+                quote! {[#(#b),*]}
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for VariantId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariantId::Int(v) => write!(f, "{}", v),
+            VariantId::Bytes(b) => write!(f, "b\"{}\"", b.escape_ascii()),
+        }
+    }
+}
+
+/// Validates an enum's variant ids and returns its id type with the id of every variant
+///
+/// Variants without an id take the previous integer id + 1 (C style). Without an `id_type`, the
+/// smallest integer that fits every id is used, or `[u8; N]` for byte string ids.
+pub(crate) fn get_enum_ids(data: &DataEnum, attrs: &EnumAttributes) -> (syn::Type, Vec<VariantId>) {
+    let mut seen_ids: HashMap<String, String> = HashMap::new();
+    let mut ids = Vec::with_capacity(data.variants.len());
+    let mut next_variant_id: i128 = 0;
+    let mut bytes_len = None;
+    for variant in data.variants.iter() {
+        let var_attrs: VariantAttributes = FromVariant::from_variant(variant).unwrap();
+        let variant_name = variant.ident.to_string();
+        let variant_id = match var_attrs.id {
+            Some(syn::Lit::ByteStr(ref b)) => VariantId::Bytes(b.value()),
+            Some(syn::Lit::Int(ref i)) => VariantId::Int(i.base10_parse().unwrap()),
+            Some(syn::Lit::Str(ref i)) => match i.value().trim().parse() {
+                Ok(v) => VariantId::Int(v),
+                Err(_) => panic!("#[sp(id = \"{}\")] on variant '{}' is not an integer", i.value(), variant_name),
+            },
+            Some(_) => panic!("#[sp(id)] on variant '{}' must be an integer or a byte string", variant_name),
+            None => VariantId::Int(next_variant_id),
+        };
+        match variant_id {
+            VariantId::Int(v) => next_variant_id = v + 1,
+            VariantId::Bytes(ref b) => {
+                if b.is_empty() || bytes_len.is_some_and(|l| l != b.len()) {
+                    panic!("#[sp(id)] on variant '{}' must be a byte string as long as the ids of the other variants", variant_name);
+                }
+                bytes_len = Some(b.len());
+            }
+        }
+
+        if let Some(v) = seen_ids.insert(variant_id.to_string(), variant_name.clone()) {
+            panic!("Field {} has the same ID as {} : {}", variant_name, v, variant_id);
+        }
+        ids.push((variant_name, variant_id));
+    }
+
+    let id_type = match bytes_len {
+        Some(len) => {
+            if let Some((name, _)) = ids.iter().find(|(_, id)| matches!(id, VariantId::Int(_))) {
+                panic!("Variant '{}' needs a byte string #[sp(id)] like the other variants", name);
+            }
+            let array_type = quote! {[u8; #len]}.to_string();
+            match attrs.id_type {
+                Some(ref t) if syn::parse_str::<syn::Type>(t).map(|t| quote! {#t}.to_string()).ok() != Some(array_type) => {
+                    panic!("Enums with byte string ids must use id_type = \"[u8; {}]\" instead of {}", len, t)
+                }
+                _ => format!("[u8; {}]", len),
+            }
+        }
+        None => {
+            let min = ids.iter().filter_map(|(_, id)| match id {
+                VariantId::Int(v) => Some(*v),
+                _ => None,
+            }).min().unwrap_or(0);
+            let max = ids.iter().filter_map(|(_, id)| match id {
+                VariantId::Int(v) => Some(*v),
+                _ => None,
+            }).max().unwrap_or(0);
+            let id_type = match attrs.id_type {
+                Some(ref s) => s.clone(),
+                None => smallest_type_for_range(min, max).to_string(),
+            };
+            match int_type_range(&id_type) {
+                Some((type_min, type_max)) if min < type_min || max > type_max => {
+                    panic!("The variant ids {}..={} do not fit in the id_type {}", min, max, id_type)
+                }
+                Some(_) => {}
+                None => panic!("Unknown id_type {}. Valid values are integers or [u8; N] for byte string ids", id_type),
+            }
+            id_type
+        }
+    };
+
+    (syn::parse_str(&id_type).unwrap(), ids.into_iter().map(|(_, id)| id).collect())
+}
+
+/// Returns the range of values of an integer type
+fn int_type_range(type_str: &str) -> Option<(i128, i128)> {
+    Some(match type_str {
+        "u8" => (0, u8::MAX as _),
+        "u16" => (0, u16::MAX as _),
+        "u32" => (0, u32::MAX as _),
+        "u64" | "usize" => (0, u64::MAX as _),
+        "u128" => (0, i128::MAX),
+        "i8" => (i8::MIN as _, i8::MAX as _),
+        "i16" => (i16::MIN as _, i16::MAX as _),
+        "i32" => (i32::MIN as _, i32::MAX as _),
+        "i64" | "isize" => (i64::MIN as _, i64::MAX as _),
+        "i128" => (i128::MIN, i128::MAX),
+        _ => return None,
+    })
+}
+
+/// Returns the smallest integer type that holds every value between `min` and `max`.
+/// Unsigned types are used when there are no negative values
+pub(crate) fn smallest_type_for_range(min: i128, max: i128) -> &'static str {
+    let candidates: &[&'static str] = if min < 0 {
+        &["i8", "i16", "i32", "i64", "i128"]
     } else {
-        "u128"
+        &["u8", "u16", "u32", "u64", "u128"]
+    };
+    for t in candidates {
+        let (type_min, type_max) = int_type_range(t).unwrap();
+        if type_min <= min && max <= type_max {
+            return t;
+        }
     }
+    "i128"
 }
 
 pub (crate) enum AllowFields {
@@ -169,6 +966,7 @@ pub(crate) fn split_custom_attr(
     cur_field_idx: usize,
     prefix: Option<&str>,
     allow_field: AllowFields,
+    uninit_fields: bool,
 ) -> std::result::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream), Box<dyn std::error::Error>> {
     let mut fn_name = String::new();
     let mut field_names = Vec::new();
@@ -247,6 +1045,12 @@ pub(crate) fn split_custom_attr(
         }
         let simple_name = generate_field_name(field, idx, None, false).to_string();
         let real_name = generate_field_name(field, idx, prefix, false);
+        // When reading, fields are still held in `&mut MaybeUninit<T>`
+        let field_ref = if uninit_fields {
+            quote!{unsafe { #real_name.assume_init_ref() }}
+        } else {
+            quote!{& #real_name}
+        };
 
         sorted_names.push(simple_name.clone());
         
@@ -256,10 +1060,10 @@ pub(crate) fn split_custom_attr(
                 if let AllowFields::AfterCurrentAsNone = allow_field {
                     quote!{None}
                 } else {
-                    quote!{Some(& #real_name)}
+                    quote!{Some(#field_ref)}
                 }
             } else {
-                quote!{& #real_name}
+                field_ref
             }
         );
     }
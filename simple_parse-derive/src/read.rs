@@ -6,7 +6,125 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{parse_quote, Data, DataEnum, DeriveInput, Fields};
 
-pub(crate) fn generate(input: &mut DeriveInput) -> proc_macro2::TokenStream {
+/// Where the generated code reads its bytes from
+#[derive(Clone, Copy)]
+pub(crate) enum ReadSource {
+    /// `SpRead` over a `Read`
+    Reader,
+    /// `SpReadRaw` over a `&[u8]`
+    Slice,
+    /// `SpAsyncRead` over a `SpAsyncReader`
+    AsyncReader,
+    /// `SpSeekRead` over a `SpSeekReader`
+    SeekReader,
+}
+
+impl ReadSource {
+    /// Generates the code that reads a `typ` into `dst: &mut MaybeUninit<typ>`
+    /// 
+    /// `map_err` is applied to the result before returning any error
+    fn read_call(self, typ: &syn::Type, dst: &TokenStream, map_err: &TokenStream) -> TokenStream {
+        match self {
+            ReadSource::Reader => quote! {<#typ>::inner_from_reader(src, ctx, #dst)#map_err?;},
+            ReadSource::SeekReader => quote! {<#typ as ::simple_parse::SpSeekRead>::inner_from_seek_reader(src, ctx, #dst)#map_err?;},
+            ReadSource::Slice => quote! {<#typ>::inner_from_slice(src, ctx, #dst)#map_err?;},
+            ReadSource::AsyncReader => quote! {#dst.write(<#typ>::inner_from_async_reader(src, ctx).await #map_err?);},
+        }
+    }
+
+    /// Generates the code that reads `num_bits` into a u64
+    fn bits_call(self, num_bits: u32, order: &TokenStream) -> TokenStream {
+        match self {
+            ReadSource::Reader | ReadSource::SeekReader => quote! {::simple_parse::bits_from_reader(src, ctx, #num_bits, #order)},
+            ReadSource::Slice => quote! {::simple_parse::bits_from_slice(src, ctx, #num_bits, #order)},
+            ReadSource::AsyncReader => quote! {::simple_parse::bits_from_async_reader(src, ctx, #num_bits, #order).await},
+        }
+    }
+
+    /// Generates the code that skips `num_bytes` of padding
+    fn pad_call(self, num_bytes: &TokenStream, padding: &FieldPadding, map_err: &TokenStream) -> TokenStream {
+        let pad = padding.pad;
+        let strict = padding.strict;
+        let skip = match self {
+            ReadSource::Reader | ReadSource::SeekReader => quote! {::simple_parse::pad_from_reader(src, ctx, __sp_pad, #pad, #strict)},
+            ReadSource::Slice => quote! {::simple_parse::pad_from_slice(src, ctx, __sp_pad, #pad, #strict)},
+            ReadSource::AsyncReader => quote! {::simple_parse::pad_from_async_reader(src, ctx, __sp_pad, #pad, #strict).await},
+        };
+        quote! {
+            let __sp_pad = #num_bytes;
+            #skip #map_err?;
+        }
+    }
+
+    /// Generates the code that reads the bytes of a `magic` or `const` and makes sure they match
+    fn magic_call(self, magic: &syn::LitByteStr, map_err: &TokenStream) -> TokenStream {
+        let num_bytes = magic.value().len();
+        let array_type: syn::Type = parse_quote! {[u8; #num_bytes]};
+        let read_magic = self.read_call(&array_type, &quote! {(&mut __sp_magic)}, map_err);
+        quote! {
+            {
+                let mut __sp_magic = MaybeUninit::uninit();
+                #read_magic
+                ::simple_parse::check_magic(unsafe { __sp_magic.assume_init_ref() }, #magic)#map_err?;
+            }
+        }
+    }
+
+    /// Generates the code that reads the rest of the input as a `typ`
+    fn eof_call(self, typ: &syn::Type) -> TokenStream {
+        match self {
+            ReadSource::Reader | ReadSource::SeekReader => quote! {::simple_parse::eof_from_reader::<#typ, _>(src, ctx)},
+            ReadSource::Slice => quote! {::simple_parse::eof_from_slice::<#typ>(src, ctx)},
+            ReadSource::AsyncReader => quote! {::simple_parse::eof_from_async_reader::<#typ, _>(src, ctx).await},
+        }
+    }
+
+    /// Generates the code that reads a `typ` up to the `term` bytes
+    fn until_call(self, typ: &syn::Type, term: &syn::LitByteStr, keep: bool) -> TokenStream {
+        match self {
+            ReadSource::Reader | ReadSource::SeekReader => quote! {::simple_parse::until_from_reader::<#typ, _>(src, ctx, #term, #keep)},
+            ReadSource::Slice => quote! {::simple_parse::until_from_slice::<#typ>(src, ctx, #term, #keep)},
+            ReadSource::AsyncReader => quote! {::simple_parse::until_from_async_reader::<#typ, _>(src, ctx, #term, #keep).await},
+        }
+    }
+
+    /// Generates the code that starts passing the bytes that are read to a checksum
+    fn tap_open(self, checksum: &FieldChecksum) -> TokenStream {
+        let tap = checksum.tap_ident();
+        let algo = &checksum.algo;
+        match self {
+            ReadSource::Slice => quote! {let #tap: &[u8] = *src;},
+            _ => quote! {
+                let mut #tap = ::simple_parse::SpChecksumTap::<_, #algo>::new(src);
+                let src = &mut #tap;
+            },
+        }
+    }
+
+    /// Generates the code that computes a checksum once its covered fields were read
+    fn tap_close(self, checksum: &FieldChecksum) -> TokenStream {
+        let tap = checksum.tap_ident();
+        let sum = checksum.sum_ident();
+        let algo = &checksum.algo;
+        match self {
+            ReadSource::Slice => quote! {let #sum = ::simple_parse::slice_checksum::<#algo>(#tap, src);},
+            _ => quote! {let (src, #sum) = #tap.finish();},
+        }
+    }
+
+    fn is_async(self) -> bool {
+        matches!(self, ReadSource::AsyncReader)
+    }
+
+    fn is_seekable(self) -> bool {
+        matches!(self, ReadSource::SeekReader)
+    }
+}
+
+/// Lifetime of the input bytes for `SpReadRaw`
+const RAW_LIFETIME: &str = "'__sp";
+
+pub(crate) fn generate(input: &mut DeriveInput, source: ReadSource) -> proc_macro2::TokenStream {
     let mut log_call: TokenStream = TokenStream::new();
     let mut init_code: TokenStream;
 
@@ -15,20 +133,39 @@ pub(crate) fn generate(input: &mut DeriveInput) -> proc_macro2::TokenStream {
         // Parse as a struct
         Data::Struct(ref contents) => {
             let attrs: StructAttributes = FromDeriveInput::from_derive_input(&input).unwrap();
-            let (field_init, mut field_names) = generate_fields_read(&contents.fields, attrs.endian.as_deref());
-            
-            // Cast every field of Self to a &mut MaybeUninit<_>
-            init_code = quote!{
-                let p = dst.as_mut_ptr() as *mut Self;
-            };
-            for (name, typ) in field_names.drain(..) {
-                init_code.extend(quote!{
-                    let #name: &mut MaybeUninit::<#typ> = unsafe {&mut *(addr_of_mut!((*p).#name) as *mut MaybeUninit::<#typ>)};
-                })
+            let parent = input.ident.to_string();
+            let (mut field_init, mut field_names) = generate_fields_read(&contents.fields, attrs.endian.as_deref(), attrs.len_type.as_deref(), attrs.bit_order.as_deref(), source, &parent, None);
+            if let Some(magic) = magic_attr(&parent, attrs.magic.as_ref(), attrs.endian.as_deref()) {
+                let read_magic = source.magic_call(&magic, &TokenStream::new());
+                field_init = quote! {
+                    #read_magic
+                    #field_init
+                };
             }
+            
+            if source.is_async() {
+                // The future owns the fields until Self can be returned
+                let is_tuple = matches!(&contents.fields, syn::Fields::Unnamed(_));
+                let (stack_vars, self_content) = generate_stack_fields(&field_names, is_tuple);
+                init_code = quote!{
+                    #stack_vars
+                    #field_init
+                    let v = unsafe { Self #self_content };
+                };
+            } else {
+                // Cast every field of Self to a &mut MaybeUninit<_>
+                init_code = quote!{
+                    let p = dst.as_mut_ptr() as *mut Self;
+                };
+                for (name, typ) in field_names.drain(..) {
+                    init_code.extend(quote!{
+                        let #name: &mut MaybeUninit::<#typ> = unsafe {&mut *(addr_of_mut!((*p).#name) as *mut MaybeUninit::<#typ>)};
+                    })
+                }
 
-            // Code that initializes each field
-            init_code.extend(field_init);
+                // Code that initializes each field
+                init_code.extend(field_init);
+            }
     
             if cfg!(feature = "verbose") {
                 let name = &input.ident;
@@ -40,10 +177,16 @@ pub(crate) fn generate(input: &mut DeriveInput) -> proc_macro2::TokenStream {
         // Parse as enum
         Data::Enum(ref contents) => {
             let attrs = FromDeriveInput::from_derive_input(&input).unwrap();
-            let parsed_enum = generate_enum_read(contents, &attrs);
+            let parsed_enum = generate_enum_read(contents, &attrs, source, &input.ident.to_string());
             
-            init_code = quote!{
-                dst.write({#parsed_enum});
+            init_code = if source.is_async() {
+                quote!{
+                    let v = {#parsed_enum};
+                }
+            } else {
+                quote!{
+                    dst.write({#parsed_enum});
+                }
             };
 
             if cfg!(feature = "verbose") {
@@ -57,27 +200,123 @@ pub(crate) fn generate(input: &mut DeriveInput) -> proc_macro2::TokenStream {
         _ => unimplemented!("Cannot derive SpRead on this type"),
     };
 
-    add_trait_bounds(&mut input.generics, parse_quote! {::simple_parse::SpRead});
+    // Record where Self starts for `#[sp(offset_from = "struct")]`
+    let (save_start, restore_start) = if source.is_seekable() {
+        (
+            quote! {let __sp_struct_start = ::core::mem::replace(&mut ctx.struct_start, ctx.cursor);},
+            quote! {ctx.struct_start = __sp_struct_start;},
+        )
+    } else {
+        (TokenStream::new(), TokenStream::new())
+    };
+
+    // The context is restored even when reading a field fails
     let name = &input.ident;
-    let (_impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fn_body = quote! {
+        use ::core::{ptr::addr_of_mut, mem::MaybeUninit};
 
-    // Generate impl block
-    let res = quote! {
-        impl ::simple_parse::SpRead for #name #ty_generics #where_clause {
-            fn inner_from_reader<'a, R: ::std::io::Read + ?Sized>(
-                src: &mut R,
-                ctx: &mut ::simple_parse::SpCtx,
-                dst: &'a mut ::core::mem::MaybeUninit<Self>,
-            ) -> ::std::result::Result<&'a mut Self, ::simple_parse::SpError>
-            {
-                use ::core::{ptr::addr_of_mut, mem::MaybeUninit};
+        #log_call
 
-                #log_call
+        ctx.enter()?;
+        #save_start
+        #[allow(clippy::redundant_closure_call)]
+        let __sp_res = (|| -> ::core::result::Result<(), ::simple_parse::SpError> {
+            #init_code
+            Ok(())
+        })();
+        #restore_start
+        ctx.leave();
+        __sp_res?;
+
+        unsafe {
+            Ok(dst.assume_init_mut())
+        }
+    };
 
-                #init_code
-                
-                unsafe {
-                    Ok(dst.assume_init_mut())
+    // Generate impl block
+    let res = match source {
+        ReadSource::Reader => {
+            add_trait_bounds(&mut input.generics, parse_quote! {::simple_parse::SpRead});
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            quote! {
+                impl #impl_generics ::simple_parse::SpRead for #name #ty_generics #where_clause {
+                    fn inner_from_reader<'__dst, R: ::simple_parse::SpReader + ?Sized>(
+                        src: &mut R,
+                        ctx: &mut ::simple_parse::SpCtx,
+                        dst: &'__dst mut ::core::mem::MaybeUninit<Self>,
+                    ) -> ::core::result::Result<&'__dst mut Self, ::simple_parse::SpError>
+                    {
+                        #fn_body
+                    }
+                }
+            }
+        }
+        ReadSource::SeekReader => {
+            add_trait_bounds(&mut input.generics, parse_quote! {::simple_parse::SpSeekRead});
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            quote! {
+                impl #impl_generics ::simple_parse::SpSeekRead for #name #ty_generics #where_clause {
+                    fn inner_from_seek_reader<'__dst, R: ::simple_parse::SpSeekReader + ?Sized>(
+                        src: &mut R,
+                        ctx: &mut ::simple_parse::SpCtx,
+                        dst: &'__dst mut ::core::mem::MaybeUninit<Self>,
+                    ) -> ::core::result::Result<&'__dst mut Self, ::simple_parse::SpError>
+                    {
+                        #fn_body
+                    }
+                }
+            }
+        }
+        ReadSource::AsyncReader => {
+            add_trait_bounds(&mut input.generics, parse_quote! {::simple_parse::SpAsyncRead});
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            quote! {
+                impl #impl_generics ::simple_parse::SpAsyncRead for #name #ty_generics #where_clause {
+                    async fn inner_from_async_reader<R: ::simple_parse::SpAsyncReader + ?Sized>(
+                        src: &mut R,
+                        ctx: &mut ::simple_parse::SpCtx,
+                    ) -> ::core::result::Result<Self, ::simple_parse::SpError>
+                    {
+                        use ::core::mem::MaybeUninit;
+
+                        #log_call
+
+                        // The context is restored even when reading a field fails
+                        ctx.enter()?;
+                        let __sp_res: ::core::result::Result<Self, ::simple_parse::SpError> = async {
+                            #init_code
+                            Ok(v)
+                        }.await;
+                        ctx.leave();
+                        __sp_res
+                    }
+                }
+            }
+        }
+        ReadSource::Slice => {
+            let raw_lifetime = syn::Lifetime::new(RAW_LIFETIME, proc_macro2::Span::call_site());
+            add_trait_bounds(&mut input.generics, parse_quote! {::simple_parse::SpReadRaw<#raw_lifetime>});
+
+            // The input bytes must outlive every lifetime of Self
+            let mut raw_generics = input.generics.clone();
+            let mut raw_param = syn::LifetimeDef::new(raw_lifetime.clone());
+            for lifetime in input.generics.lifetimes() {
+                raw_param.bounds.push(lifetime.lifetime.clone());
+            }
+            raw_generics.params.insert(0, syn::GenericParam::Lifetime(raw_param));
+
+            let (impl_generics, _, _) = raw_generics.split_for_impl();
+            let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+            quote! {
+                impl #impl_generics ::simple_parse::SpReadRaw<#raw_lifetime> for #name #ty_generics #where_clause {
+                    fn inner_from_slice<'__dst>(
+                        src: &mut &#raw_lifetime [u8],
+                        ctx: &mut ::simple_parse::SpCtx,
+                        dst: &'__dst mut ::core::mem::MaybeUninit<Self>,
+                    ) -> ::core::result::Result<&'__dst mut Self, ::simple_parse::SpError>
+                    {
+                        #fn_body
+                    }
                 }
             }
         }
@@ -90,8 +329,9 @@ pub(crate) fn generate(input: &mut DeriveInput) -> proc_macro2::TokenStream {
 }
 
 /// Generates code that parses bytes into a struct
-fn generate_fields_read(fields: &Fields, endian: Option<&str>) -> (TokenStream, Vec<(TokenStream, syn::Type)>) {
-    
+/// 
+/// Errors are tagged with `parent` and the field name (prefixed by `variant` for enums)
+fn generate_fields_read(fields: &Fields, endian: Option<&str>, len_type: Option<&str>, bit_order: Option<&str>, source: ReadSource, parent: &str, variant: Option<&str>) -> (TokenStream, Vec<(TokenStream, syn::Type)>) {
     let num_fields = fields.len();
     let mut init_code = TokenStream::new();
     let mut field_names = Vec::with_capacity(num_fields);
@@ -112,6 +352,10 @@ fn generate_fields_read(fields: &Fields, endian: Option<&str>) -> (TokenStream,
     let mut len_field_idx = Vec::with_capacity(fields.len());
     len_field_idx.resize(fields.len(), None);
 
+    // holds a field's `len` expression and the fields it uses
+    let mut len_exprs = Vec::with_capacity(fields.len());
+    len_exprs.resize(fields.len(), None);
+
     let mut string_field_names = Vec::with_capacity(fields.len());
 
     // Iterate through all fields and save #[sp(len)] references
@@ -121,29 +365,94 @@ fn generate_fields_read(fields: &Fields, endian: Option<&str>) -> (TokenStream,
         // save the simple field name for each field seen so far
         string_field_names.push(generate_field_name(field, idx, None, false).to_string());
 
-        if let Some(count_field_name) = field_attrs.len.as_ref() {
+        if let Some((count_field_name, is_byte_len)) = len_attr(&string_field_names[idx], &field_attrs) {
+            if !is_field_ref(count_field_name) {
+                let attr_name = if is_byte_len { "byte_len" } else { "len" };
+                let (expr, used) = parse_field_expr(attr_name, &string_field_names[idx], count_field_name, &string_field_names, idx);
+                len_exprs[idx] = Some((expr, used, is_byte_len));
+                continue;
+            }
+
             let mut found_idx = idx;
             for i in 0..idx {
-                if count_field_name.as_str() == string_field_names[i].as_str() {
+                if count_field_name == string_field_names[i].as_str() {
                     found_idx = i;
                     break;
                 }
             }
             // `len` field not found
             if found_idx == idx {
-                panic!("#[sp(len)] annotation on field '{}' refers to an unknown field '{}'. Valid values are {:?}", &string_field_names[idx], count_field_name, &string_field_names[..idx]);
+                let attr_name = if is_byte_len { "byte_len" } else { "len" };
+                panic!("#[sp({})] annotation on field '{}' refers to an unknown field '{}'. Valid values are {:?}", attr_name, &string_field_names[idx], count_field_name, &string_field_names[..idx]);
             }
 
             // Save link from current field to `len` field
-            len_field_idx[idx] = Some(found_idx);
+            len_field_idx[idx] = Some((found_idx, is_byte_len));
         }
     }
 
+    let checksums = checked_checksums(&fields, &string_field_names);
+
+    let bit_order = bit_order_variant(bit_order);
+    // Whether the previous field was a bit field
+    let mut in_bits = false;
+
     let mut prev_endian = false;
     for (idx, field) in fields.iter().enumerate() {
         let field_name = generate_field_name(field, idx, None, false);
         let field_type = strip_lifetimes(&field.ty);
         let field_attrs: FieldAttributes = FromField::from_field(field).unwrap();
+        let map_err = field_error_context(field, idx, &field_type, parent, variant);
+        let num_bits = checked_bits(&string_field_names[idx], &field_type, &field_attrs);
+        let fixed_len = checked_fixed_len(&string_field_names[idx], &field_attrs);
+        let eof = checked_eof(&string_field_names[idx], &field_attrs, idx + 1 == num_fields);
+        let until = checked_until(&string_field_names[idx], &field_attrs);
+        let cond = checked_cond(&string_field_names[idx], &field_type, &field_attrs);
+        let constant = checked_const(&string_field_names[idx], &field_attrs);
+        let padding = checked_padding(&string_field_names[idx], &field_attrs);
+        let offset = checked_offset(&string_field_names[idx], &field_attrs, source.is_seekable());
+        let checksum = checksums.iter().find(|c| c.idx == idx);
+
+        // Drop the unused bits once a group of bit fields ends
+        if in_bits && num_bits.is_none() {
+            init_code.extend(quote! {
+                ctx.bits = ::simple_parse::SpBitCursor::default();
+            });
+        }
+        in_bits = num_bits.is_some();
+
+        // Pass the bytes of the covered fields to their checksums
+        for checksum in checksums_opened_at(&checksums, idx) {
+            init_code.extend(source.tap_open(checksum));
+        }
+
+        // Move to the position of the field
+        if let Some(ref offset) = offset {
+            let (expr, used) = parse_field_expr("offset", &string_field_names[idx], offset.expr, &string_field_names, idx);
+            let bindings = read_bindings(&used, &field_names);
+            let eval = expr_eval(&expr, &bindings, &quote! {usize});
+            let eval = if offset.from_struct {
+                quote! {#eval.and_then(|o| ctx.struct_start.checked_add(o))}
+            } else {
+                eval
+            };
+            let seek = quote! {::simple_parse::seek_reader_to(src, ctx, __sp_offset)#map_err?};
+            let seek = if offset.restore {
+                quote! {let __sp_prev_cursor = #seek;}
+            } else {
+                quote! {#seek;}
+            };
+            init_code.extend(quote! {
+                #[allow(clippy::redundant_closure_call)]
+                let __sp_offset = #eval.ok_or(::simple_parse::SpError::InvalidBytes)#map_err?;
+                #seek
+            });
+        }
+
+        // Skip the padding before the field
+        if let Some(num_bytes) = padding.as_ref().and_then(|p| p.before_len()) {
+            init_code.extend(source.pad_call(&num_bytes, padding.as_ref().unwrap(), &map_err));
+        }
 
         // Get this field's endianness
         let is_field_le = match field_attrs.endian {
@@ -152,9 +461,28 @@ fn generate_fields_read(fields: &Fields, endian: Option<&str>) -> (TokenStream,
         };
 
         // Get this field's sp(len) 
-        if let Some(field_idx) = len_field_idx[idx] {
+        let mut byte_len = None;
+        if let Some((field_idx, is_byte_len)) = len_field_idx[idx] {
             let count_field_name = &field_names[field_idx].0;
-            init_code.extend(quote! {ctx.len = Some(unsafe{*(#count_field_name.assume_init_mut())} as _);});
+            let count_value = quote! {unsafe{*(#count_field_name.assume_init_mut())}};
+            if is_byte_len {
+                byte_len = Some(count_value);
+            } else {
+                init_code.extend(quote! {ctx.len = Some(#count_value as _);});
+            }
+        } else if let Some((ref expr, ref used, is_byte_len)) = len_exprs[idx] {
+            // Evaluate the expression over the fields that were already read
+            let bindings = read_bindings(used, &field_names);
+            let eval = expr_eval(expr, &bindings, &quote! {usize});
+            init_code.extend(quote! {
+                #[allow(clippy::redundant_closure_call)]
+                let __sp_len = #eval.ok_or(::simple_parse::SpError::InvalidBytes)#map_err?;
+            });
+            if is_byte_len {
+                byte_len = Some(quote! {__sp_len});
+            } else {
+                init_code.extend(quote! {ctx.len = Some(__sp_len);});
+            }
         }
 
         // Fixup endianness if it changed 
@@ -165,45 +493,280 @@ fn generate_fields_read(fields: &Fields, endian: Option<&str>) -> (TokenStream,
             });
         }
 
+        // Override the count prefix of this field and its nested types
+        let field_len_type = field_attrs.len_type.as_deref().or(len_type);
+        if let Some(lt) = field_len_type {
+            let lt = len_type_variant(lt);
+            init_code.extend(quote! {
+                let __sp_len_type = ctx.len_type;
+                ctx.len_type = #lt;
+            });
+        }
+
+        // Async sources need their own custom reader
+        let custom_reader = if source.is_async() {
+            if field_attrs.reader.is_some() && field_attrs.async_reader.is_none() {
+                panic!("Field '{}' has a custom reader but no #[sp(async_reader)]", &string_field_names[idx]);
+            }
+            field_attrs.async_reader.as_ref()
+        } else {
+            field_attrs.reader.as_ref()
+        };
+        let await_call = if source.is_async() {
+            quote! {.await}
+        } else {
+            TokenStream::new()
+        };
+
         // Initialize the current field
-        init_code.extend(quote! {
-            <#field_type>::inner_from_reader(src, ctx, #field_name)?;
-        });
-        
+        match custom_reader {
+            Some(s) => {
+                let (fn_name, dependent_fields) = match split_custom_attr(s, &fields, idx, None, AllowFields::BeforeCurrent, true) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        panic!("Invalid custom reader for field '{}', {}", &string_field_names[idx], e);
+                    }
+                };
+                init_code.extend(quote! {
+                    #field_name.write(#fn_name(#dependent_fields src, ctx)#await_call #map_err?);
+                    // Dont propagate `len` to the next fields if the custom reader ignored it
+                    ctx.len = None;
+                });
+            }
+            None if num_bits.is_some() => {
+                let num_bits = num_bits.unwrap();
+                let read_bits = source.bits_call(num_bits, &bit_order);
+                init_code.extend(quote! {
+                    #field_name.write(<#field_type as ::simple_parse::SpBits>::from_bits(#read_bits #map_err?, #num_bits)#map_err?);
+                });
+            }
+            None if byte_len.is_some() => {
+                // Read items until the number of bytes is reached
+                let byte_len = byte_len.unwrap();
+                let item_type: syn::Type = parse_quote! {<#field_type as ::simple_parse::SpByteLen>::Item};
+                let read_item = source.read_call(&item_type, &quote! {(&mut __sp_item)}, &map_err);
+                init_code.extend(quote! {
+                    let __sp_byte_len = <usize as ::core::convert::TryFrom<_>>::try_from(#byte_len)
+                        .map_err(|_| ::simple_parse::SpError::InvalidBytes)#map_err?;
+                    ctx.check_bytes(__sp_byte_len)#map_err?;
+                    let __sp_end = ctx.cursor.checked_add(__sp_byte_len).ok_or(::simple_parse::SpError::InvalidBytes)#map_err?;
+                    let mut __sp_items = <#field_type as ::core::default::Default>::default();
+                    let mut __sp_count: usize = 0;
+                    while ctx.cursor < __sp_end {
+                        let __sp_start = ctx.cursor;
+                        __sp_count += 1;
+                        ctx.check_count(__sp_count)#map_err?;
+                        ctx.check_alloc(::core::mem::size_of::<#item_type>())#map_err?;
+                        let mut __sp_item = MaybeUninit::uninit();
+                        #read_item
+                        ::simple_parse::SpByteLen::push_item(&mut __sp_items, unsafe { __sp_item.assume_init() });
+                        // Items that take no bytes would never reach the end
+                        if ctx.cursor == __sp_start {
+                            Err::<(), _>(::simple_parse::SpError::InvalidBytes)#map_err?;
+                        }
+                    }
+                    // The last item went past the number of bytes
+                    if ctx.cursor != __sp_end {
+                        Err::<(), _>(::simple_parse::SpError::InvalidBytes)#map_err?;
+                    }
+                    #field_name.write(__sp_items);
+                });
+            }
+            None if cond.is_some() => {
+                // Only read T when the condition is true
+                let (expr, inner_type) = cond.unwrap();
+                let (expr, used) = parse_field_expr("cond", &string_field_names[idx], expr, &string_field_names, idx);
+                let bindings = read_bindings(&used, &field_names);
+                let eval = expr_eval(&expr, &bindings, &quote! {bool});
+                let read_some = source.read_call(&inner_type, &quote! {(&mut __sp_some)}, &map_err);
+                init_code.extend(quote! {
+                    #[allow(clippy::redundant_closure_call)]
+                    let __sp_cond = #eval.ok_or(::simple_parse::SpError::InvalidBytes)#map_err?;
+                    if __sp_cond {
+                        let mut __sp_some = MaybeUninit::uninit();
+                        #read_some
+                        #field_name.write(Some(unsafe { __sp_some.assume_init() }));
+                    } else {
+                        #field_name.write(None);
+                    }
+                });
+            }
+            None if until.is_some() && is_text_type(&field_type) => {
+                let (term, keep) = until.unwrap();
+                let term = syn::LitByteStr::new(term.as_bytes(), proc_macro2::Span::call_site());
+                let until_call = source.until_call(&field_type, &term, keep);
+                init_code.extend(quote! {
+                    #field_name.write(#until_call #map_err?);
+                });
+            }
+            None if until.is_some() => {
+                // Read items until one of them is the terminator
+                let (term, keep) = until.unwrap();
+                let term = until_elem(&string_field_names[idx], term);
+                let item_type: syn::Type = parse_quote! {<#field_type as ::simple_parse::SpByteLen>::Item};
+                let read_item = source.read_call(&item_type, &quote! {(&mut __sp_item)}, &map_err);
+                let push_item = quote! {::simple_parse::SpByteLen::push_item(&mut __sp_items, __sp_item);};
+                let handle_item = if keep {
+                    quote! {
+                        #push_item
+                        if __sp_end {
+                            break;
+                        }
+                    }
+                } else {
+                    quote! {
+                        if __sp_end {
+                            break;
+                        }
+                        #push_item
+                    }
+                };
+                init_code.extend(quote! {
+                    let __sp_until: #item_type = #term;
+                    let mut __sp_items = <#field_type as ::core::default::Default>::default();
+                    let mut __sp_count: usize = 0;
+                    loop {
+                        __sp_count += 1;
+                        ctx.check_count(__sp_count)#map_err?;
+                        ctx.check_alloc(::core::mem::size_of::<#item_type>())#map_err?;
+                        let mut __sp_item = MaybeUninit::uninit();
+                        #read_item
+                        let __sp_item = unsafe { __sp_item.assume_init() };
+                        let __sp_end = __sp_item == __sp_until;
+                        #handle_item
+                    }
+                    #field_name.write(__sp_items);
+                });
+            }
+            None if constant.is_some() => {
+                // Check the bytes then store the constant itself
+                let lit = constant.unwrap();
+                let magic = literal_bytes("const", &string_field_names[idx], lit, Some(&field_type), is_field_le);
+                let read_magic = source.magic_call(&magic, &map_err);
+                let value = match lit {
+                    syn::Lit::ByteStr(_) => quote! {*#lit},
+                    _ => quote! {#lit},
+                };
+                init_code.extend(quote! {
+                    #read_magic
+                    #field_name.write(#value);
+                });
+            }
+            None if checksum.is_some() => {
+                // Compare the field with the checksum of the bytes it covers
+                let sum = checksum.unwrap().sum_ident();
+                init_code.extend(source.read_call(&field_type, &field_name, &map_err));
+                init_code.extend(quote! {
+                    ::simple_parse::check_checksum(unsafe { *#field_name.assume_init_ref() }, #sum)#map_err?;
+                });
+            }
+            None if eof => {
+                let eof_call = source.eof_call(&field_type);
+                init_code.extend(quote! {
+                    #field_name.write(#eof_call #map_err?);
+                });
+            }
+            None if fixed_len.is_some() => {
+                // Read the whole slot then strip the padding
+                let (num_bytes, pad) = fixed_len.unwrap();
+                let array_type: syn::Type = parse_quote! {[u8; #num_bytes]};
+                let read_fixed = source.read_call(&array_type, &quote! {(&mut __sp_fixed)}, &map_err);
+                init_code.extend(quote! {
+                    let mut __sp_fixed = MaybeUninit::uninit();
+                    #read_fixed
+                    #field_name.write(::simple_parse::from_fixed_bytes::<#field_type>(unsafe { __sp_fixed.assume_init_ref() }, #pad, ctx)#map_err?);
+                });
+            }
+            None if field_attrs.varint.is_some() => {
+                // Read the wire representation then make sure it fits in the field
+                let var_type: syn::Type = parse_quote! {<#field_type as ::simple_parse::SpVarInt>::Var};
+                let read_var = source.read_call(&var_type, &quote! {(&mut __sp_var)}, &map_err);
+                init_code.extend(quote! {
+                    let mut __sp_var = MaybeUninit::uninit();
+                    #read_var
+                    #field_name.write(<#field_type as ::simple_parse::SpVarInt>::from_var(unsafe { __sp_var.assume_init() })#map_err?);
+                });
+            }
+            None => {
+                init_code.extend(source.read_call(&field_type, &field_name, &map_err));
+            }
+        };
+
+        if field_len_type.is_some() {
+            init_code.extend(quote! {
+                ctx.len_type = __sp_len_type;
+            });
+        }
+
+        // Skip the padding after the field
+        if let Some(num_bytes) = padding.as_ref().and_then(|p| p.after_len()) {
+            init_code.extend(source.pad_call(&num_bytes, padding.as_ref().unwrap(), &map_err));
+        }
+
+        // Go back to where the previous field ended
+        if offset.as_ref().is_some_and(|o| o.restore) {
+            init_code.extend(quote! {
+                ::simple_parse::seek_reader_to(src, ctx, __sp_prev_cursor)#map_err?;
+            });
+        }
+
+        // Compute the checksums that cover this field last
+        for checksum in checksums_closed_at(&checksums, idx) {
+            init_code.extend(source.tap_close(checksum));
+        }
+
+        // Call the custom validator
+        if let Some(ref s) = field_attrs.validate {
+            let (fn_name, other_fields) = match split_custom_attr(s, &fields, idx, None, AllowFields::AfterCurrentAsNone, true) {
+                Ok(v) => v,
+                Err(e) => {
+                    panic!("Invalid custom validator for field '{}', {}", &string_field_names[idx], e);
+                }
+            };
+            init_code.extend(quote! {
+                ctx.is_reading = true;
+                #fn_name(unsafe { #field_name.assume_init_ref() }, #other_fields ctx)#map_err?;
+            });
+        }
+
         // Save the field name and field type for the caller
         field_names.push((field_name, field_type));
     }
 
+    if in_bits {
+        init_code.extend(quote! {
+            ctx.bits = ::simple_parse::SpBitCursor::default();
+        });
+    }
+
     (init_code, field_names)
 }
 
+/// Declares the fields that were already read and are used by an expression
+fn read_bindings(used: &[usize], field_names: &[(TokenStream, syn::Type)]) -> TokenStream {
+    let mut bindings = TokenStream::new();
+    for &i in used {
+        let (ref name, ref ty) = field_names[i];
+        bindings.extend(expr_binding(name, ty, &quote! {unsafe { #name.assume_init_ref() }}));
+    }
+    bindings
+}
+
 /// Generates the code that parse bytes into an enum variant
-fn generate_enum_read(data: &DataEnum, attrs: &EnumAttributes) -> TokenStream {
+fn generate_enum_read(data: &DataEnum, attrs: &EnumAttributes, source: ReadSource, parent: &str) -> TokenStream {
     if data.variants.is_empty() {
         panic!("Unable to derive SpRead on empty enum");
     }
 
     // Pick the best size to use for the variant IDs
-    let id_type = get_enum_id_type(data, attrs);
+    let (id_type, variant_ids) = get_enum_ids(data, attrs);
 
     // Read the id
     let mut init_code = TokenStream::new();
-    let mut next_variant_id: usize = 0;
-    for variant in data.variants.iter() {
+    for (variant, variant_id) in data.variants.iter().zip(variant_ids.iter()) {
         let var_attrs: VariantAttributes = FromVariant::from_variant(&variant).unwrap();
         let variant_name = &variant.ident;
-        let variant_id = match var_attrs.id {
-            Some(id) => {
-                next_variant_id = id + 1;
-                id
-            }
-            _ => {
-                let cur = next_variant_id;
-                next_variant_id += 1;
-                cur
-            }
-        };
-        let variant_id = syn::LitInt::new(&variant_id.to_string(), proc_macro2::Span::call_site());
+        let variant_id = variant_id.tokens();
 
         let mut read_code = TokenStream::new();
         let mut variant_content = TokenStream::new();
@@ -217,43 +780,13 @@ fn generate_enum_read(data: &DataEnum, attrs: &EnumAttributes) -> TokenStream {
                 None => attrs.endian.as_deref(),
             };
 
-            let (field_init, field_list) = generate_fields_read(&variant.fields, variant_endianness);
-
-            let mut stack_name = String::new();
-            for (idx, (name, typ)) in field_list.iter().enumerate() {
-                // We cant write directly into the enum variant as theres no way
-                // to get a pointer into the "fields"
-                // Therefore, we must create stack variables for each field and then
-                // instantiate the enum with the stack vars
-                stack_name.clear();
-                write!(&mut stack_name, "s{idx}").unwrap();
-                let s_name: TokenStream  = stack_name.parse().unwrap();
-
-                read_code.extend(quote!{
-                    let mut #s_name = MaybeUninit::<#typ>::uninit();
-                    let #name = &mut #s_name;
-                });
-
-                if is_tuple {
-                    variant_content.extend(quote!{
-                        #s_name.assume_init(),
-                    });
-                } else {
-                    variant_content.extend(quote!{
-                        #name: #s_name.assume_init(),
-                    });
-                }
-            }
+            let (field_init, field_list) = generate_fields_read(&variant.fields, variant_endianness, attrs.len_type.as_deref(), attrs.bit_order.as_deref(), source, parent, Some(&variant_name.to_string()));
 
-            if is_tuple {
-                variant_content = quote!{
-                    (#variant_content)
-                };
-            } else {
-                variant_content = quote!{
-                    {#variant_content}
-                };
-            }
+            // We cant write directly into the enum variant as theres no way
+            // to get a pointer into the "fields"
+            let (stack_vars, content) = generate_stack_fields(&field_list, is_tuple);
+            read_code.extend(stack_vars);
+            variant_content = content;
 
             read_code.extend(field_init);
         }
@@ -277,11 +810,52 @@ fn generate_enum_read(data: &DataEnum, attrs: &EnumAttributes) -> TokenStream {
         })
     }
 
+    let read_magic = match magic_attr(parent, attrs.magic.as_ref(), attrs.endian.as_deref()) {
+        Some(magic) => source.magic_call(&magic, &TokenStream::new()),
+        None => TokenStream::new(),
+    };
+    let read_id = source.read_call(&id_type, &quote!{(&mut tmp)}, &TokenStream::new());
     quote!{
+        #read_magic
         let mut tmp = MaybeUninit::uninit();
-        match *<#id_type>::inner_from_reader(src, ctx, &mut tmp)? {
+        #read_id
+        match unsafe { *tmp.assume_init_ref() } {
             #init_code
             _ => return Err(::simple_parse::SpError::UnknownEnumVariant),
         }
     }
 }
+
+/// Creates stack variables for each field and the content used to instantiate Self with them
+fn generate_stack_fields(field_list: &[(TokenStream, syn::Type)], is_tuple: bool) -> (TokenStream, TokenStream) {
+    let mut stack_vars = TokenStream::new();
+    let mut content = TokenStream::new();
+
+    let mut stack_name = String::new();
+    for (idx, (name, typ)) in field_list.iter().enumerate() {
+        stack_name.clear();
+        write!(&mut stack_name, "s{idx}").unwrap();
+        let s_name: TokenStream  = stack_name.parse().unwrap();
+
+        stack_vars.extend(quote!{
+            let mut #s_name = MaybeUninit::<#typ>::uninit();
+            let #name = &mut #s_name;
+        });
+
+        if is_tuple {
+            content.extend(quote!{
+                #s_name.assume_init(),
+            });
+        } else {
+            content.extend(quote!{
+                #name: #s_name.assume_init(),
+            });
+        }
+    }
+
+    if is_tuple {
+        (stack_vars, quote!{(#content)})
+    } else {
+        (stack_vars, quote!{{#content}})
+    }
+}
@@ -5,39 +5,183 @@ use syn::{parse_quote, Data, DataEnum, DeriveInput, Fields};
 
 use crate::*;
 
-pub fn generate(input: &mut DeriveInput) -> TokenStream {
+/// Where the bytes are written to
+#[derive(Clone, Copy)]
+pub enum WriteDest {
+    /// `SpWrite` into a `SpWriter`
+    Writer,
+    /// `SpAsyncWrite` into a `SpAsyncWriter`
+    AsyncWriter,
+    /// `SpSeekWrite` into a `SpSeekWriter`
+    SeekWriter,
+}
+
+impl WriteDest {
+    /// Generates the code that writes `val` into `dst`
+    /// 
+    /// `map_err` is applied to the result before returning any error
+    fn write_call(self, val: &TokenStream, map_err: &TokenStream) -> TokenStream {
+        match self {
+            WriteDest::Writer => quote! {#val.inner_to_writer(ctx, dst)#map_err?},
+            WriteDest::SeekWriter => quote! {#val.inner_to_seek_writer(ctx, dst)#map_err?},
+            WriteDest::AsyncWriter => quote! {#val.inner_to_async_writer(ctx, dst).await #map_err?},
+        }
+    }
+
+    /// Generates the code that writes the low `num_bits` of `val`
+    fn bits_write_call(self, val: &TokenStream, num_bits: u32, order: &TokenStream, map_err: &TokenStream) -> TokenStream {
+        match self {
+            WriteDest::Writer | WriteDest::SeekWriter => quote! {::simple_parse::bits_to_writer(#val, #num_bits, #order, ctx, dst)#map_err?},
+            WriteDest::AsyncWriter => quote! {::simple_parse::bits_to_async_writer(#val, #num_bits, #order, ctx, dst).await #map_err?},
+        }
+    }
+
+    /// Generates the code that writes the pending bits of a group of bit fields
+    fn bits_flush_call(self, order: &TokenStream) -> TokenStream {
+        match self {
+            WriteDest::Writer | WriteDest::SeekWriter => quote! {::simple_parse::bits_flush_to_writer(#order, ctx, dst)?},
+            WriteDest::AsyncWriter => quote! {::simple_parse::bits_flush_to_async_writer(#order, ctx, dst).await?},
+        }
+    }
+
+    /// Generates the code that writes `num_bytes` of padding
+    fn pad_call(self, num_bytes: &TokenStream, padding: &FieldPadding, map_err: &TokenStream) -> TokenStream {
+        let pad = padding.pad;
+        let write_pad = match self {
+            WriteDest::Writer | WriteDest::SeekWriter => quote! {::simple_parse::pad_to_writer(ctx, dst, __sp_pad, #pad)#map_err?},
+            WriteDest::AsyncWriter => quote! {::simple_parse::pad_to_async_writer(ctx, dst, __sp_pad, #pad).await #map_err?},
+        };
+        quote! {
+            let __sp_pad = #num_bytes;
+            written_len += #write_pad;
+        }
+    }
+
+    /// Generates the code that starts passing the bytes that are written to a checksum
+    fn tap_open(self, checksum: &FieldChecksum) -> TokenStream {
+        let tap = checksum.tap_ident();
+        let algo = &checksum.algo;
+        quote! {
+            let mut #tap = ::simple_parse::SpChecksumTap::<_, #algo>::new(dst);
+            let dst = &mut #tap;
+        }
+    }
 
-    let generated_code = match input.data {
+    /// Generates the code that computes a checksum once its covered fields were written
+    fn tap_close(self, checksum: &FieldChecksum) -> TokenStream {
+        let tap = checksum.tap_ident();
+        let sum = checksum.sum_ident();
+        quote! {let (dst, #sum) = #tap.finish();}
+    }
+
+    fn is_async(self) -> bool {
+        matches!(self, WriteDest::AsyncWriter)
+    }
+
+    fn is_seekable(self) -> bool {
+        matches!(self, WriteDest::SeekWriter)
+    }
+}
+
+pub fn generate(input: &mut DeriveInput, dest: WriteDest) -> TokenStream {
+
+    let mut generated_code = match input.data {
         Data::Struct(ref contents) => {
             let attrs: StructAttributes = FromDeriveInput::from_derive_input(&input).unwrap();
-            generate_fields_write(&contents.fields, Some("self"), attrs.endian.as_deref()).0
+            let parent = input.ident.to_string();
+            let write_code = generate_fields_write(&contents.fields, Some("self"), attrs.endian.as_deref(), attrs.len_type.as_deref(), attrs.bit_order.as_deref(), dest, &parent, None).0;
+            match magic_attr(&parent, attrs.magic.as_ref(), attrs.endian.as_deref()) {
+                Some(magic) => {
+                    let write_magic = dest.write_call(&quote! {(*#magic)}, &TokenStream::new());
+                    quote! {
+                        written_len += #write_magic;
+                        #write_code
+                    }
+                }
+                None => write_code,
+            }
         }
         Data::Enum(ref contents) => {
             let attrs = FromDeriveInput::from_derive_input(&input).unwrap();
-            generate_enum_write(&input, contents, attrs)
+            generate_enum_write(&input, contents, attrs, dest)
         }
         // Unhandled derive usage
         _ => unimplemented!("Cannot derive on this type"),
     };
 
-    add_trait_bounds(&mut input.generics, parse_quote! {simple_parse::SpRead});
-    let name = &input.ident;
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // Record where Self starts for `#[sp(offset_from = "struct")]`
+    // and restore it even when writing a field fails
+    if dest.is_seekable() {
+        generated_code = quote! {
+            let __sp_struct_start = ::core::mem::replace(&mut ctx.struct_start, ctx.cursor);
+            #[allow(clippy::redundant_closure_call)]
+            let __sp_res = (|| -> ::core::result::Result<(), ::simple_parse::SpError> {
+                #generated_code
+                Ok(())
+            })();
+            ctx.struct_start = __sp_struct_start;
+            __sp_res?;
+        };
+    }
 
-    let res = quote! {
-        impl #impl_generics ::simple_parse::SpWrite for #name #ty_generics #where_clause {
-            fn to_writer<W: std::io::Write + ?Sized>(&self, dst: &mut W) -> std::result::Result<usize, ::simple_parse::SpError> {
-                self.inner_to_writer(&mut ::simple_parse::SpCtx::default(), dst)
+    let name = input.ident.clone();
+
+    let res = match dest {
+        WriteDest::SeekWriter => {
+            add_trait_bounds(&mut input.generics, parse_quote! {::simple_parse::SpSeekWrite});
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            quote! {
+                impl #impl_generics ::simple_parse::SpSeekWrite for #name #ty_generics #where_clause {
+                    fn inner_to_seek_writer<W: ::simple_parse::SpSeekWriter + ?Sized>(
+                        &self,
+                        ctx: &mut ::simple_parse::SpCtx,
+                        dst: &mut W,
+                    ) -> ::core::result::Result<usize, ::simple_parse::SpError>
+                    {
+                        let mut written_len: usize = 0;
+                        #generated_code
+                        Ok(written_len)
+                    }
+                }
             }
-            fn inner_to_writer<W: std::io::Write + ?Sized>(
-                &self,
-                ctx: &mut ::simple_parse::SpCtx,
-                dst: &mut W,
-            ) -> std::result::Result<usize, ::simple_parse::SpError>
-            {
-                let mut written_len: usize = 0;
-                #generated_code
-                Ok(written_len)
+        }
+        WriteDest::AsyncWriter => {
+            add_trait_bounds(&mut input.generics, parse_quote! {::simple_parse::SpAsyncWrite});
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            quote! {
+                impl #impl_generics ::simple_parse::SpAsyncWrite for #name #ty_generics #where_clause {
+                    async fn inner_to_async_writer<W: ::simple_parse::SpAsyncWriter + ?Sized>(
+                        &self,
+                        ctx: &mut ::simple_parse::SpCtx,
+                        dst: &mut W,
+                    ) -> ::core::result::Result<usize, ::simple_parse::SpError>
+                    {
+                        let mut written_len: usize = 0;
+                        #generated_code
+                        Ok(written_len)
+                    }
+                }
+            }
+        }
+        WriteDest::Writer => {
+            add_trait_bounds(&mut input.generics, parse_quote! {::simple_parse::SpWrite});
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            quote! {
+                impl #impl_generics ::simple_parse::SpWrite for #name #ty_generics #where_clause {
+                    fn to_writer<W: ::simple_parse::SpWriter + ?Sized>(&self, dst: &mut W) -> ::core::result::Result<usize, ::simple_parse::SpError> {
+                        self.inner_to_writer(&mut ::simple_parse::SpCtx::default(), dst)
+                    }
+                    fn inner_to_writer<W: ::simple_parse::SpWriter + ?Sized>(
+                        &self,
+                        ctx: &mut ::simple_parse::SpCtx,
+                        dst: &mut W,
+                    ) -> ::core::result::Result<usize, ::simple_parse::SpError>
+                    {
+                        let mut written_len: usize = 0;
+                        #generated_code
+                        Ok(written_len)
+                    }
+                }
             }
         }
     };
@@ -53,6 +197,11 @@ fn generate_fields_write(
     fields: &Fields,
     prefix: Option<&str>,
     endian: Option<&str>,
+    len_type: Option<&str>,
+    bit_order: Option<&str>,
+    dest: WriteDest,
+    parent: &str,
+    variant: Option<&str>,
 ) -> (TokenStream, TokenStream) {
 
     let mut write_code = TokenStream::new();
@@ -71,6 +220,10 @@ fn generate_fields_write(
     let mut count_field_idx = Vec::with_capacity(fields.len());
     count_field_idx.resize(fields.len(), None);
 
+    // Holds a field's `len` expression and the fields it uses
+    let mut len_exprs = Vec::with_capacity(fields.len());
+    len_exprs.resize(fields.len(), None);
+
     let mut simple_field_names = Vec::with_capacity(fields.len());
     let fields: Vec<&syn::Field> = fields.iter().collect();
 
@@ -78,6 +231,7 @@ fn generate_fields_write(
     for (idx, field) in fields.iter().enumerate() {
         let field_attrs: FieldAttributes = FromField::from_field(&field).unwrap();
         let field_ident = generate_field_name(field, idx, prefix, false);
+        let map_err = field_error_context(field, idx, &strip_lifetimes(&field.ty), parent, variant);
         
         // save the simple field name for each field seen so far
         simple_field_names.push(generate_field_name(field, idx, None, false).to_string());
@@ -85,7 +239,7 @@ fn generate_fields_write(
         // Get custom validator
         match field_attrs.validate {
             Some(ref s) => {
-                let (fn_name, other_fields) = match split_custom_attr(s, &fields, idx, prefix, AllowFields::AfterCurrentAsSome) {
+                let (fn_name, other_fields) = match split_custom_attr(s, &fields, idx, prefix, AllowFields::AfterCurrentAsSome, false) {
                     Ok(v) => v,
                     Err(e) => {
                         panic!("Invalid custom validator for field '{}', {}", &simple_field_names[idx], e);
@@ -93,37 +247,144 @@ fn generate_fields_write(
                 };
                 write_code.extend(quote!{
                     ctx.is_reading = false;
-                    #fn_name(&#field_ident, #other_fields ctx)?;
+                    #fn_name(&#field_ident, #other_fields ctx)#map_err?;
                 })
             },
             None => {},
         };
 
-        if let Some(count_field_name) = field_attrs.len.as_ref() {
+        if let Some((count_field_name, is_byte_len)) = len_attr(&simple_field_names[idx], &field_attrs) {
+            if !is_field_ref(count_field_name) {
+                let attr_name = if is_byte_len { "byte_len" } else { "len" };
+                let (expr, used) = parse_field_expr(attr_name, &simple_field_names[idx], count_field_name, &simple_field_names, idx);
+                len_exprs[idx] = Some((expr, used, is_byte_len));
+                continue;
+            }
+
             let mut field_idx = idx;
             for i in 0..idx {
-                if count_field_name.as_str() == simple_field_names[i].as_str() {
+                if count_field_name == simple_field_names[i].as_str() {
                     field_idx = i;
                     break;
                 }
             }
             // `len` field not found
             if field_idx == idx {
-                panic!("#[sp(len)] annotation on field '{}' referers to an unknown field '{}'. Valid values are {:?}", &simple_field_names[idx], count_field_name, &simple_field_names[..idx]);
+                let attr_name = if is_byte_len { "byte_len" } else { "len" };
+                panic!("#[sp({})] annotation on field '{}' referers to an unknown field '{}'. Valid values are {:?}", attr_name, &simple_field_names[idx], count_field_name, &simple_field_names[..idx]);
             }
 
             // Save link from `len` field to this field
-            count_field_vals[field_idx] = Some(idx);
+            count_field_vals[field_idx] = Some((idx, is_byte_len));
             // Save link from this field to its `len` field
-            count_field_idx[idx] = Some(field_idx);
+            count_field_idx[idx] = Some((field_idx, is_byte_len));
         }
     }
     
+    // Parse the `calc` expressions now that every field name is known
+    let mut calc_exprs = Vec::with_capacity(fields.len());
+    for (idx, field) in fields.iter().enumerate() {
+        let field_attrs: FieldAttributes = FromField::from_field(&field).unwrap();
+        if field_attrs.constant.is_some() && count_field_vals[idx].is_some() {
+            panic!("#[sp(const)] on field '{}' cannot be used as it is already the len of another field", &simple_field_names[idx]);
+        }
+        if field_attrs.checksum.is_some() && count_field_vals[idx].is_some() {
+            panic!("#[sp(checksum)] on field '{}' cannot be used as it is already the len of another field", &simple_field_names[idx]);
+        }
+        calc_exprs.push(match field_attrs.calc {
+            Some(ref s) => {
+                if count_field_vals[idx].is_some() {
+                    panic!("#[sp(calc)] on field '{}' cannot be used as it is already the len of another field", &simple_field_names[idx]);
+                }
+                if field_attrs.writer.is_some() || field_attrs.async_writer.is_some() {
+                    panic!("#[sp(calc)] on field '{}' cannot be combined with a custom writer", &simple_field_names[idx]);
+                }
+                Some(parse_field_expr("calc", &simple_field_names[idx], s, &simple_field_names, fields.len()))
+            }
+            None => None,
+        });
+    }
+
+    // Whether a field was replaced by a local variable holding the value that is written
+    let mut written_locals = vec![false; fields.len()];
+    // Declares the fields used by an expression, using the values that are written when known
+    let expr_bindings = |used: &[usize], written_locals: &[bool]| {
+        let mut bindings = TokenStream::new();
+        for &i in used {
+            let name = generate_field_name(fields[i], i, None, false);
+            let value = if written_locals[i] {
+                name.clone()
+            } else {
+                generate_field_name(fields[i], i, prefix, false)
+            };
+            bindings.extend(expr_binding(&name, &fields[i].ty, &quote! {&#value}));
+        }
+        bindings
+    };
+
+    let checksums = checked_checksums(&fields, &simple_field_names);
+
+    let bit_order = bit_order_variant(bit_order);
+    // Whether the previous field was a bit field
+    let mut in_bits = false;
+
     let mut got_count = false;
     // Generate write call for every field
     for (idx, field) in fields.iter().enumerate() {
         let field_attrs: FieldAttributes = FromField::from_field(&field).unwrap();
         let field_ident = generate_field_name(field, idx, prefix, false);
+        let map_err = field_error_context(field, idx, &strip_lifetimes(&field.ty), parent, variant);
+        let num_bits = checked_bits(&simple_field_names[idx], &strip_reference(&field.ty), &field_attrs);
+        let fixed_len = checked_fixed_len(&simple_field_names[idx], &field_attrs);
+        let eof = checked_eof(&simple_field_names[idx], &field_attrs, idx + 1 == fields.len());
+        let until = checked_until(&simple_field_names[idx], &field_attrs);
+        let cond = checked_cond(&simple_field_names[idx], &strip_reference(&field.ty), &field_attrs);
+        let constant = checked_const(&simple_field_names[idx], &field_attrs);
+        let padding = checked_padding(&simple_field_names[idx], &field_attrs);
+        let offset = checked_offset(&simple_field_names[idx], &field_attrs, dest.is_seekable());
+        let checksum = checksums.iter().find(|c| c.idx == idx);
+
+        // Write the last partial byte once a group of bit fields ends
+        if in_bits && num_bits.is_none() {
+            let flush = dest.bits_flush_call(&bit_order);
+            write_code.extend(quote! {
+                written_len += #flush;
+            });
+        }
+        in_bits = num_bits.is_some();
+
+        // Pass the bytes of the covered fields to their checksums
+        for checksum in checksums_opened_at(&checksums, idx) {
+            write_code.extend(dest.tap_open(checksum));
+        }
+
+        // Move to the position of the field
+        if let Some(ref offset) = offset {
+            let (expr, used) = parse_field_expr("offset", &simple_field_names[idx], offset.expr, &simple_field_names, idx);
+            let bindings = expr_bindings(&used, &written_locals);
+            let eval = expr_eval(&expr, &bindings, &quote! {usize});
+            let eval = if offset.from_struct {
+                quote! {#eval.and_then(|o| ctx.struct_start.checked_add(o))}
+            } else {
+                eval
+            };
+            let seek = quote! {::simple_parse::seek_writer_to(dst, ctx, __sp_offset)#map_err?};
+            let seek = if offset.restore {
+                quote! {let __sp_prev_cursor = #seek;}
+            } else {
+                quote! {#seek;}
+            };
+            write_code.extend(quote! {
+                #[allow(clippy::redundant_closure_call)]
+                let __sp_offset = #eval.ok_or(::simple_parse::SpError::CountFieldOverflow)#map_err?;
+                #seek
+            });
+        }
+
+        // Fill the padding before the field
+        if let Some(num_bytes) = padding.as_ref().and_then(|p| p.before_len()) {
+            write_code.extend(dest.pad_call(&num_bytes, padding.as_ref().unwrap(), &map_err));
+        }
 
         field_list.extend(
             quote!{
@@ -136,10 +397,34 @@ fn generate_fields_write(
             Some(ref e) => is_lower_endian(e),
         }; 
 
+        // Fills the padding after the field
+        let mut after_field = match padding.as_ref().and_then(|p| p.after_len()) {
+            Some(num_bytes) => dest.pad_call(&num_bytes, padding.as_ref().unwrap(), &map_err),
+            None => TokenStream::new(),
+        };
+        // Then goes back to where the previous field ended
+        if offset.as_ref().is_some_and(|o| o.restore) {
+            after_field.extend(quote! {
+                ::simple_parse::seek_writer_to(dst, ctx, __sp_prev_cursor)#map_err?;
+            });
+        }
+        // Then computes the checksums that cover this field last
+        let closed = checksums_closed_at(&checksums, idx);
+        if !closed.is_empty() && num_bits.is_some() {
+            // The last partial byte of the group of bit fields is covered
+            let flush = dest.bits_flush_call(&bit_order);
+            after_field.extend(quote! {
+                written_len += #flush;
+            });
+        }
+        for checksum in closed {
+            after_field.extend(dest.tap_close(checksum));
+        }
+
         let len_value;
 
         // If this field is a `len` field, write the len instead
-        if let Some(field_idx) = count_field_vals[idx] {
+        if let Some((field_idx, is_byte_len)) = count_field_vals[idx] {
             let content_field = fields[field_idx];
             let content_ty = strip_reference(&content_field.ty);
             let content_ident = generate_field_name(content_field, field_idx, prefix, false);
@@ -150,14 +435,22 @@ fn generate_fields_write(
             if !got_count {
                 got_count = true;
                 write_code.extend(quote!{
-                    use std::convert::TryInto;
+                    use ::core::convert::TryInto;
                 });
             }
 
             let content_ty_str = quote!{#content_ty}.to_string();
             let count_ty_str = quote!{#count_type}.to_string();
             let count_decl = 
-            if content_ty_str.starts_with("Option <") {
+            if is_byte_len {
+                let size_call = byte_size_call(content_field, field_idx, prefix, len_type, dest, parent, variant);
+                quote!{
+                    let #count_ident: #count_type = match #size_call.try_into() {
+                        Ok(v) => v,
+                        Err(e) => return Err(::simple_parse::SpError::CountFieldOverflow),
+                    };
+                }
+            } else if content_ty_str.starts_with("Option <") {
                 if count_ty_str == "bool" {
                     quote!{
                         let #count_ident: #count_type = #content_ident.is_some();
@@ -179,62 +472,305 @@ fn generate_fields_write(
                     };
                 }
             };
+            let write_count = if let Some(num_bits) = num_bits {
+                dest.bits_write_call(&quote! {<#count_type as ::simple_parse::SpBits>::to_bits(&#count_ident, #num_bits)#map_err?}, num_bits, &bit_order, &map_err)
+            } else if field_attrs.varint.is_some() {
+                dest.write_call(&quote! {<#count_type as ::simple_parse::SpVarInt>::to_var(&#count_ident)}, &map_err)
+            } else {
+                dest.write_call(&count_ident, &map_err)
+            };
             // Create temporary var to hold the real `len` value
             // then write this value
             write_code.extend(quote! {
                 #count_decl
                 ctx.is_little_endian = #is_output_le;
-                written_len += #count_ident.inner_to_writer(ctx, dst)?;
+                written_len += #write_count;
+                #after_field
             });
+            written_locals[idx] = true;
             continue;
-        } else if let Some(field_idx) = count_field_idx[idx] {
+        } else if let Some((ref expr, ref used, is_byte_len)) = len_exprs[idx] {
+            // The current field's `len` is an expression.
+            // Make sure it matches the items that are written
+            let bindings = expr_bindings(used, &written_locals);
+            let eval = expr_eval(expr, &bindings, &quote! {usize});
+            let field_ty = strip_reference(&field.ty);
+            let field_ty_str = quote!{#field_ty}.to_string();
+            let actual_len = if is_byte_len {
+                byte_size_call(field, idx, prefix, len_type, dest, parent, variant)
+            } else if field_ty_str.starts_with("Option <") {
+                quote!{#field_ident.is_some() as usize}
+            } else {
+                quote!{#field_ident.len()}
+            };
+            write_code.extend(quote! {
+                #[allow(clippy::redundant_closure_call)]
+                let __sp_len = #eval.ok_or(::simple_parse::SpError::CountFieldOverflow)#map_err?;
+                let __sp_actual_len = #actual_len;
+                if __sp_len != __sp_actual_len {
+                    Err::<(), _>(::simple_parse::SpError::LenMismatch { expected: __sp_len, len: __sp_actual_len })#map_err?;
+                }
+            });
+            len_value = if field_ty_str.starts_with("Option <") {
+                quote!{Some(__sp_len)}
+            } else {
+                quote!{Some(#field_ident.len())}
+            };
+        } else if let Some((_, true)) = count_field_idx[idx] {
+            // The current field is annotated with `byte_len`.
+            // Only write the items
+            len_value = quote!{Some(#field_ident.len())};
+        } else if let Some((field_idx, false)) = count_field_idx[idx] {
             // The current field is annotated with `len`.
             // Pass the `len` field's value as Some to its `inner_to_writer()`
             let count_field = fields[field_idx];
             let count_ident = generate_field_name(count_field, field_idx, None, false);
             len_value = quote!{Some(#count_ident as usize)};
+        } else if eof {
+            // The current field takes the rest of the input, only write its bytes
+            let field_type = strip_reference(&field.ty);
+            len_value = quote!{Some(<#field_type as ::simple_parse::SpFixedLen>::fixed_bytes(&#field_ident).len())};
         } else {
             // current field is not annotated with `len`
             len_value = quote!{None};
         }
 
+        // Compute the value of the field from the other fields
+        let value_ident = match calc_exprs[idx] {
+            _ if checksum.is_some() => {
+                // Write the checksum of the covered bytes instead of the field
+                let name = generate_field_name(field, idx, None, false);
+                let sum = checksum.unwrap().sum_ident();
+                let ty = &field.ty;
+                write_code.extend(quote! {
+                    let #name: #ty = #sum;
+                });
+                written_locals[idx] = true;
+                name
+            }
+            Some((ref expr, ref used)) => {
+                let name = generate_field_name(field, idx, None, false);
+                let ty = &field.ty;
+                let bindings = expr_bindings(used, &written_locals);
+                let eval = expr_eval(expr, &bindings, &quote! {#ty});
+                write_code.extend(quote! {
+                    #[allow(clippy::redundant_closure_call)]
+                    let #name: #ty = #eval.ok_or(::simple_parse::SpError::CountFieldOverflow)#map_err?;
+                });
+                written_locals[idx] = true;
+                name
+            }
+            None => field_ident.clone(),
+        };
+
+        // Async destinations need their own custom writer
+        let custom_writer = if dest.is_async() {
+            if field_attrs.writer.is_some() && field_attrs.async_writer.is_none() {
+                panic!("Field '{}' has a custom writer but no #[sp(async_writer)]", &simple_field_names[idx]);
+            }
+            field_attrs.async_writer.as_ref()
+        } else {
+            field_attrs.writer.as_ref()
+        };
+
         // Pick between custom write or default
-        let write_call = match field_attrs.writer {
-            Some(ref s) => {
-                let (fn_name, dependent_fields) = match split_custom_attr(s, &fields, idx, prefix, AllowFields::All) {
+        let write_call = match custom_writer {
+            Some(s) => {
+                let (fn_name, dependent_fields) = match split_custom_attr(s, &fields, idx, prefix, AllowFields::All, false) {
                     Ok(v) => v,
                     Err(e) => {
                         panic!("Invalid custom writer for field '{}', {}", &simple_field_names[idx], e);
                     }
                 };
-                quote!{
-                    #fn_name(&#field_ident, #dependent_fields ctx, dst)
+                if dest.is_async() {
+                    quote!{
+                        #fn_name(&#field_ident, #dependent_fields ctx, dst).await #map_err?
+                    }
+                } else {
+                    quote!{
+                        #fn_name(&#field_ident, #dependent_fields ctx, dst)#map_err?
+                    }
+                }
+            }
+            None if num_bits.is_some() => {
+                let num_bits = num_bits.unwrap();
+                let field_type = strip_reference(&field.ty);
+                dest.bits_write_call(&quote! {<#field_type as ::simple_parse::SpBits>::to_bits(&#value_ident, #num_bits)#map_err?}, num_bits, &bit_order, &map_err)
+            }
+            None if fixed_len.is_some() => {
+                let (num_bytes, pad) = fixed_len.unwrap();
+                let field_type = strip_reference(&field.ty);
+                dest.write_call(&quote! {::simple_parse::to_fixed_bytes::<#field_type, #num_bytes>(&#value_ident, #pad)#map_err?}, &map_err)
+            }
+            None if field_attrs.varint.is_some() => {
+                let field_type = strip_reference(&field.ty);
+                dest.write_call(&quote! {<#field_type as ::simple_parse::SpVarInt>::to_var(&#value_ident)}, &map_err)
+            }
+            None if cond.is_some() => {
+                // Only write T when the condition is true
+                let (expr, _) = cond.unwrap();
+                let (expr, used) = parse_field_expr("cond", &simple_field_names[idx], expr, &simple_field_names, idx);
+                let bindings = expr_bindings(&used, &written_locals);
+                let eval = expr_eval(&expr, &bindings, &quote! {bool});
+                let write_some = dest.write_call(&quote! {__sp_some}, &map_err);
+                quote! {
+                    {
+                        #[allow(clippy::redundant_closure_call)]
+                        let __sp_cond = #eval.ok_or(::simple_parse::SpError::CountFieldOverflow)#map_err?;
+                        match #value_ident.as_ref() {
+                            Some(__sp_some) if __sp_cond => #write_some,
+                            None if !__sp_cond => 0,
+                            _ => Err::<usize, _>(::simple_parse::SpError::CondMismatch)#map_err?,
+                        }
+                    }
                 }
             }
-            None => {
+            None if until.is_some() && is_text_type(&strip_reference(&field.ty)) => {
+                let (term, keep) = until.unwrap();
+                let term = syn::LitByteStr::new(term.as_bytes(), proc_macro2::Span::call_site());
+                let field_type = strip_reference(&field.ty);
+                let write_bytes = dest.write_call(&quote! {__sp_bytes.as_slice()}, &map_err);
                 quote! {
-                    #field_ident.inner_to_writer(ctx, dst)
+                    {
+                        let __sp_bytes = ::simple_parse::until_to_bytes::<#field_type>(&#value_ident, #term, #keep)#map_err?;
+                        ctx.len = Some(__sp_bytes.len());
+                        #write_bytes
+                    }
+                }
+            }
+            None if until.is_some() => {
+                // Write the items without a count followed by the terminator
+                let (term, keep) = until.unwrap();
+                let term = until_elem(&simple_field_names[idx], term);
+                let field_type = strip_reference(&field.ty);
+                let write_items = dest.write_call(&value_ident, &map_err);
+                let write_term = dest.write_call(&quote! {__sp_until}, &map_err);
+                if keep {
+                    // A kept terminator can only be the last item
+                    quote! {
+                        {
+                            let __sp_until: <#field_type as ::simple_parse::SpByteLen>::Item = #term;
+                            let mut __sp_has_end = false;
+                            for (__sp_idx, __sp_item) in #value_ident.iter().enumerate() {
+                                if *__sp_item == __sp_until {
+                                    if __sp_idx + 1 == #value_ident.len() {
+                                        __sp_has_end = true;
+                                        continue;
+                                    }
+                                    Err::<(), _>(::simple_parse::SpError::AmbiguousTerminator)#map_err?;
+                                }
+                            }
+                            ctx.len = Some(#value_ident.len());
+                            let mut __sp_written = #write_items;
+                            if !__sp_has_end {
+                                __sp_written += #write_term;
+                            }
+                            __sp_written
+                        }
+                    }
+                } else {
+                    quote! {
+                        {
+                            let __sp_until: <#field_type as ::simple_parse::SpByteLen>::Item = #term;
+                            for __sp_item in #value_ident.iter() {
+                                if *__sp_item == __sp_until {
+                                    Err::<(), _>(::simple_parse::SpError::AmbiguousTerminator)#map_err?;
+                                }
+                            }
+                            ctx.len = Some(#value_ident.len());
+                            #write_items + #write_term
+                        }
+                    }
                 }
             }
+            None if constant.is_some() => {
+                // Always write the constant, whatever the field holds
+                let field_type = strip_reference(&field.ty);
+                let magic = literal_bytes("const", &simple_field_names[idx], constant.unwrap(), Some(&field_type), is_output_le);
+                dest.write_call(&quote! {(*#magic)}, &map_err)
+            }
+            None if eof => {
+                let field_type = strip_reference(&field.ty);
+                dest.write_call(&quote! {<#field_type as ::simple_parse::SpFixedLen>::fixed_bytes(&#value_ident)}, &map_err)
+            }
+            None => dest.write_call(&value_ident, &map_err),
         };
 
         // Add the generated code for this field
+        match field_attrs.len_type.as_deref().or(len_type) {
+            Some(lt) => {
+                // Override the count prefix of this field and its nested types
+                let lt = len_type_variant(lt);
+                write_code.extend(quote! {
+                    ctx.is_little_endian = #is_output_le;
+                    ctx.len = #len_value;
+                    let __sp_len_type = ctx.len_type;
+                    ctx.len_type = #lt;
+                    written_len += #write_call;
+                    ctx.len_type = __sp_len_type;
+                    #after_field
+                })
+            }
+            None => write_code.extend(quote! {
+                ctx.is_little_endian = #is_output_le;
+                ctx.len = #len_value;
+                written_len += #write_call;
+                #after_field
+            }),
+        }
+    }
+
+    if in_bits {
+        let flush = dest.bits_flush_call(&bit_order);
         write_code.extend(quote! {
-            ctx.is_little_endian = #is_output_le;
-            ctx.len = #len_value;
-            written_len += #write_call?;
-        })
+            written_len += #flush;
+        });
     }
 
     (write_code, field_list)
 }
 
+/// Generates the code that computes the number of bytes taken by the items of a `byte_len` field
+/// by writing them into a sink
+fn byte_size_call(
+    field: &syn::Field,
+    idx: usize,
+    prefix: Option<&str>,
+    len_type: Option<&str>,
+    dest: WriteDest,
+    parent: &str,
+    variant: Option<&str>,
+) -> TokenStream {
+    let field_attrs: FieldAttributes = FromField::from_field(field).unwrap();
+    let field_ident = generate_field_name(field, idx, prefix, false);
+    let map_err = field_error_context(field, idx, &strip_lifetimes(&field.ty), parent, variant);
+    let set_len_type = match field_attrs.len_type.as_deref().or(len_type) {
+        Some(lt) => {
+            let lt = len_type_variant(lt);
+            quote! {__sp_ctx.len_type = #lt;}
+        }
+        None => TokenStream::new(),
+    };
+    let size_call = match dest {
+        WriteDest::Writer | WriteDest::SeekWriter => quote! {#field_ident.inner_to_writer(&mut __sp_ctx, &mut ::simple_parse::SpSink)#map_err?},
+        WriteDest::AsyncWriter => quote! {#field_ident.inner_to_async_writer(&mut __sp_ctx, &mut ::simple_parse::SpSink).await #map_err?},
+    };
+    quote! {
+        {
+            let mut __sp_ctx = ctx.clone();
+            __sp_ctx.len = Some(#field_ident.len());
+            #set_len_type
+            #size_call
+        }
+    }
+}
+
 /// Generates the code that matches the current enum variant and dumps bytes
 /// for each of its fields
-fn generate_enum_write(input: &DeriveInput, data: &DataEnum, attrs: EnumAttributes) -> TokenStream {
+fn generate_enum_write(input: &DeriveInput, data: &DataEnum, attrs: EnumAttributes, dest: WriteDest) -> TokenStream {
     let name = &input.ident;
 
-    let id_type = get_enum_id_type(data, &attrs);
+    let (id_type, variant_ids) = get_enum_ids(data, &attrs);
 
     let default_is_le: bool = match attrs.endian {
         None => cfg!(target_endian = "little"),
@@ -242,26 +778,14 @@ fn generate_enum_write(input: &DeriveInput, data: &DataEnum, attrs: EnumAttribut
     };
 
     let mut variant_code_gen = TokenStream::new();
-    let mut auto_variant_id:usize = 0;
     // Create a match case for every variant
-    for variant in data.variants.iter() {
+    for (variant, variant_id) in data.variants.iter().zip(variant_ids.iter()) {
         let var_attrs: VariantAttributes = FromVariant::from_variant(&variant).unwrap();
         let variant_name = &variant.ident;
-        let variant_id = match var_attrs.id {
-            Some(id) => {
-                auto_variant_id = id + 1;
-                id
-            },
-            _ => {
-                let cur = auto_variant_id;
-                auto_variant_id += 1;
-                cur
-            },
-        };
-        let variant_id = syn::LitInt::new(&variant_id.to_string(), proc_macro2::Span::call_site());
+        let variant_id = variant_id.tokens();
 
         let (write_code, field_list) = if !variant.fields.is_empty() {
-            let (write, list) = generate_fields_write(&variant.fields, None, var_attrs.endian.as_deref());
+            let (write, list) = generate_fields_write(&variant.fields, None, var_attrs.endian.as_deref(), attrs.len_type.as_deref(), attrs.bit_order.as_deref(), dest, &name.to_string(), Some(&variant_name.to_string()));
             (write, 
                 if let syn::Fields::Unnamed(_r) = &variant.fields {
                     quote!{
@@ -277,17 +801,29 @@ fn generate_enum_write(input: &DeriveInput, data: &DataEnum, attrs: EnumAttribut
             (TokenStream::new(), TokenStream::new())
         };
 
+        let write_id = dest.write_call(&quote!{var_id}, &TokenStream::new());
         variant_code_gen.extend(quote! {
             #name::#variant_name#field_list => {
                 let mut var_id: #id_type = #variant_id;
                 ctx.is_little_endian = #default_is_le;
-                written_len += var_id.inner_to_writer(ctx, dst)?;
+                written_len += #write_id;
                 #write_code
             },
         });
     }
 
+    let write_magic = match magic_attr(&name.to_string(), attrs.magic.as_ref(), attrs.endian.as_deref()) {
+        Some(magic) => {
+            let write_magic = dest.write_call(&quote! {(*#magic)}, &TokenStream::new());
+            quote! {
+                written_len += #write_magic;
+            }
+        }
+        None => TokenStream::new(),
+    };
+
     quote! {
+        #write_magic
         match self {
             #variant_code_gen
         };
@@ -0,0 +1,271 @@
+use core::convert::TryFrom;
+
+use crate::*;
+
+/// Order in which consecutive `#[sp(bits)]` fields are packed into bytes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SpBitOrder {
+    /// The first field uses the most significant bits of the first byte (network order)
+    #[default]
+    Msb,
+    /// The first field uses the least significant bits of the first byte
+    Lsb,
+}
+
+/// Byte that is partially consumed by a group of bit fields
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SpBitCursor {
+    /// Bits left to read, or bits waiting to be written. They are held in the low bits for
+    /// [SpBitOrder::Msb] and from the low bits upwards for [SpBitOrder::Lsb]
+    pub byte: u8,
+    /// Number of valid bits in `byte`
+    pub len: u8,
+}
+
+fn mask(num_bits: u32) -> u64 {
+    if num_bits >= 64 {
+        u64::MAX
+    } else {
+        (1 << num_bits) - 1
+    }
+}
+
+impl SpBitCursor {
+    /// Consumes up to `num_bits` from the current byte and adds them to `v`
+    fn take(&mut self, v: &mut u64, got: u32, num_bits: u32, order: SpBitOrder) -> u32 {
+        let avail = self.len as u32;
+        let k = core::cmp::min(num_bits - got, avail);
+        let byte = self.byte as u64;
+        match order {
+            SpBitOrder::Msb => {
+                let chunk = (byte >> (avail - k)) & mask(k);
+                *v = (*v << k) | chunk;
+            }
+            SpBitOrder::Lsb => {
+                *v |= (byte & mask(k)) << got;
+                self.byte = (byte >> k) as u8;
+            }
+        }
+        self.len -= k as u8;
+        k
+    }
+
+    /// Adds as many bits of `v` as possible to the current byte
+    fn put(&mut self, v: u64, sent: u32, num_bits: u32, order: SpBitOrder) -> u32 {
+        let k = core::cmp::min(num_bits - sent, 8 - self.len as u32);
+        let byte = self.byte as u64;
+        self.byte = match order {
+            SpBitOrder::Msb => ((byte << k) | ((v >> (num_bits - sent - k)) & mask(k))) as u8,
+            SpBitOrder::Lsb => (byte | (((v >> sent) & mask(k)) << self.len)) as u8,
+        };
+        self.len += k as u8;
+        k
+    }
+
+    /// Returns the pending byte padded with zeroes
+    fn padded(&self, order: SpBitOrder) -> u8 {
+        match order {
+            SpBitOrder::Msb => ((self.byte as u32) << (8 - self.len as u32)) as u8,
+            SpBitOrder::Lsb => self.byte,
+        }
+    }
+}
+
+/// Integers and bool that can be annotated with `#[sp(bits = N)]`
+#[doc(hidden)]
+pub trait SpBits: Sized {
+    /// Converts the `num_bits` read from the input
+    fn from_bits(v: u64, num_bits: u32) -> Result<Self, SpError>;
+    /// Converts self into `num_bits`, failing if it does not fit
+    fn to_bits(&self, num_bits: u32) -> Result<u64, SpError>;
+}
+
+macro_rules! unsigned_bits {
+    ($($typ:ty),+) => {
+        $(
+        impl SpBits for $typ {
+            fn from_bits(v: u64, _num_bits: u32) -> Result<Self, SpError> {
+                <$typ>::try_from(v).map_err(|_| SpError::InvalidBytes)
+            }
+            fn to_bits(&self, num_bits: u32) -> Result<u64, SpError> {
+                let v = *self as u64;
+                if v & !mask(num_bits) != 0 {
+                    return Err(SpError::BitFieldOverflow);
+                }
+                Ok(v)
+            }
+        }
+        )+
+    };
+}
+unsigned_bits!(u8, u16, u32, u64, usize);
+
+macro_rules! signed_bits {
+    ($($typ:ty),+) => {
+        $(
+        impl SpBits for $typ {
+            fn from_bits(v: u64, num_bits: u32) -> Result<Self, SpError> {
+                // Sign extend the two's complement value
+                let shift = 64 - num_bits;
+                let v = ((v << shift) as i64) >> shift;
+                <$typ>::try_from(v).map_err(|_| SpError::InvalidBytes)
+            }
+            fn to_bits(&self, num_bits: u32) -> Result<u64, SpError> {
+                let v = *self as i64;
+                let shift = 64 - num_bits;
+                if (v << shift) >> shift != v {
+                    return Err(SpError::BitFieldOverflow);
+                }
+                Ok(v as u64 & mask(num_bits))
+            }
+        }
+        )+
+    };
+}
+signed_bits!(i8, i16, i32, i64, isize);
+
+impl SpBits for bool {
+    fn from_bits(v: u64, _num_bits: u32) -> Result<Self, SpError> {
+        match v {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SpError::InvalidBytes),
+        }
+    }
+    fn to_bits(&self, _num_bits: u32) -> Result<u64, SpError> {
+        Ok(*self as u64)
+    }
+}
+
+/// Reads `num_bits` from the input, continuing from the partially consumed byte if any
+#[doc(hidden)]
+pub fn bits_from_reader<R: SpReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+    num_bits: u32,
+    order: SpBitOrder,
+) -> Result<u64, SpError> {
+    let mut v = 0;
+    let mut got = 0;
+    while got < num_bits {
+        if ctx.bits.len == 0 {
+            ctx.bits.byte = *<u8>::inner_from_reader(src, ctx, &mut MaybeUninit::uninit())?;
+            ctx.bits.len = 8;
+        }
+        got += ctx.bits.take(&mut v, got, num_bits, order);
+    }
+    Ok(v)
+}
+
+/// Reads `num_bits` from the slice, continuing from the partially consumed byte if any
+#[doc(hidden)]
+pub fn bits_from_slice(
+    src: &mut &[u8],
+    ctx: &mut SpCtx,
+    num_bits: u32,
+    order: SpBitOrder,
+) -> Result<u64, SpError> {
+    let mut v = 0;
+    let mut got = 0;
+    while got < num_bits {
+        if ctx.bits.len == 0 {
+            ctx.bits.byte = *<u8>::inner_from_slice(src, ctx, &mut MaybeUninit::uninit())?;
+            ctx.bits.len = 8;
+        }
+        got += ctx.bits.take(&mut v, got, num_bits, order);
+    }
+    Ok(v)
+}
+
+/// Reads `num_bits` from the asynchronous reader, continuing from the partially consumed byte if any
+#[doc(hidden)]
+#[cfg(feature = "async")]
+pub async fn bits_from_async_reader<R: SpAsyncReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+    num_bits: u32,
+    order: SpBitOrder,
+) -> Result<u64, SpError> {
+    let mut v = 0;
+    let mut got = 0;
+    while got < num_bits {
+        if ctx.bits.len == 0 {
+            ctx.bits.byte = <u8>::inner_from_async_reader(src, ctx).await?;
+            ctx.bits.len = 8;
+        }
+        got += ctx.bits.take(&mut v, got, num_bits, order);
+    }
+    Ok(v)
+}
+
+/// Writes the low `num_bits` of `v`. Bytes are only written once all of their bits are known
+#[doc(hidden)]
+pub fn bits_to_writer<W: SpWriter + ?Sized>(
+    v: u64,
+    num_bits: u32,
+    order: SpBitOrder,
+    ctx: &mut SpCtx,
+    dst: &mut W,
+) -> Result<usize, SpError> {
+    let mut written = 0;
+    let mut sent = 0;
+    while sent < num_bits {
+        sent += ctx.bits.put(v, sent, num_bits, order);
+        if ctx.bits.len == 8 {
+            let byte = core::mem::take(&mut ctx.bits).byte;
+            written += byte.inner_to_writer(ctx, dst)?;
+        }
+    }
+    Ok(written)
+}
+
+/// Writes the low `num_bits` of `v`. Bytes are only written once all of their bits are known
+#[doc(hidden)]
+#[cfg(feature = "async")]
+pub async fn bits_to_async_writer<W: SpAsyncWriter + ?Sized>(
+    v: u64,
+    num_bits: u32,
+    order: SpBitOrder,
+    ctx: &mut SpCtx,
+    dst: &mut W,
+) -> Result<usize, SpError> {
+    let mut written = 0;
+    let mut sent = 0;
+    while sent < num_bits {
+        sent += ctx.bits.put(v, sent, num_bits, order);
+        if ctx.bits.len == 8 {
+            let byte = core::mem::take(&mut ctx.bits).byte;
+            written += byte.inner_to_async_writer(ctx, dst).await?;
+        }
+    }
+    Ok(written)
+}
+
+/// Writes the pending bits of a group of bit fields padded with zeroes
+#[doc(hidden)]
+pub fn bits_flush_to_writer<W: SpWriter + ?Sized>(
+    order: SpBitOrder,
+    ctx: &mut SpCtx,
+    dst: &mut W,
+) -> Result<usize, SpError> {
+    if ctx.bits.len == 0 {
+        return Ok(0);
+    }
+    let byte = core::mem::take(&mut ctx.bits).padded(order);
+    byte.inner_to_writer(ctx, dst)
+}
+
+/// Writes the pending bits of a group of bit fields padded with zeroes
+#[doc(hidden)]
+#[cfg(feature = "async")]
+pub async fn bits_flush_to_async_writer<W: SpAsyncWriter + ?Sized>(
+    order: SpBitOrder,
+    ctx: &mut SpCtx,
+    dst: &mut W,
+) -> Result<usize, SpError> {
+    if ctx.bits.len == 0 {
+        return Ok(0);
+    }
+    let byte = core::mem::take(&mut ctx.bits).padded(order);
+    byte.inner_to_async_writer(ctx, dst).await
+}
@@ -0,0 +1,187 @@
+use crate::*;
+
+/// An algorithm used by `#[sp(checksum)]` fields
+///
+/// The bytes covered by the field are passed to `update` as they are read or written. Custom
+/// algorithms can be used by giving the path to their type (e.g. `#[sp(checksum = "Adler32")]`).
+pub trait SpChecksum: Default {
+    /// Type of the fields that hold the checksum
+    type Output: Copy + PartialEq + Into<u64>;
+
+    /// Adds `bytes` to the checksum
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the checksum of every byte seen so far
+    fn finish(&self) -> Self::Output;
+}
+
+/// Builds the lookup table of a reflected CRC
+const fn crc_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Adds `bytes` to a reflected CRC
+fn crc_update(mut crc: u32, table: &[u32; 256], bytes: &[u8]) -> u32 {
+    for b in bytes {
+        crc = (crc >> 8) ^ table[((crc ^ *b as u32) & 0xFF) as usize];
+    }
+    crc
+}
+
+const CRC16_TABLE: [u32; 256] = crc_table(0xA001);
+const CRC32_TABLE: [u32; 256] = crc_table(0xEDB8_8320);
+
+/// CRC-16/ARC, usually simply called CRC-16 (`checksum = "crc16"`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Crc16(u16);
+
+impl SpChecksum for Crc16 {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.0 = crc_update(self.0 as u32, &CRC16_TABLE, bytes) as u16;
+    }
+
+    fn finish(&self) -> u16 {
+        self.0
+    }
+}
+
+/// CRC-32/ISO-HDLC, as used by Ethernet, zip and PNG (`checksum = "crc32"`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self(u32::MAX)
+    }
+}
+
+impl SpChecksum for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.0 = crc_update(self.0, &CRC32_TABLE, bytes);
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// Wrapping sum of every byte (`checksum = "sum8"`)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sum8(u8);
+
+impl SpChecksum for Sum8 {
+    type Output = u8;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = self.0.wrapping_add(*b);
+        }
+    }
+
+    fn finish(&self) -> u8 {
+        self.0
+    }
+}
+
+/// Passes every byte that goes through a reader or writer to a [SpChecksum]
+///
+/// Derived types wrap their input/output with it while the fields covered by a `checksum` are read/written.
+pub struct SpChecksumTap<'a, T: ?Sized, C> {
+    inner: &'a mut T,
+    checksum: C,
+}
+
+impl<'a, T: ?Sized, C: SpChecksum> SpChecksumTap<'a, T, C> {
+    /// Starts a checksum over the bytes that go through `inner`
+    pub fn new(inner: &'a mut T) -> Self {
+        Self {
+            inner,
+            checksum: C::default(),
+        }
+    }
+
+    /// Returns the wrapped reader/writer without going through the checksum
+    #[cfg(feature = "std")]
+    pub(crate) fn inner_mut(&mut self) -> &mut T {
+        self.inner
+    }
+
+    /// Returns the wrapped reader/writer and the checksum of the bytes that went through it
+    pub fn finish(self) -> (&'a mut T, C::Output) {
+        (self.inner, self.checksum.finish())
+    }
+}
+
+impl<R: SpReader + ?Sized, C: SpChecksum> SpReader for SpChecksumTap<'_, R, C> {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SpError> {
+        self.inner.read_bytes(buf)?;
+        self.checksum.update(buf);
+        Ok(())
+    }
+
+    fn read_some(&mut self, buf: &mut [u8]) -> Result<usize, SpError> {
+        let len = self.inner.read_some(buf)?;
+        self.checksum.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+impl<W: SpWriter + ?Sized, C: SpChecksum> SpWriter for SpChecksumTap<'_, W, C> {
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError> {
+        self.inner.write_bytes(buf)?;
+        self.checksum.update(buf);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R: SpAsyncReader + ?Sized, C: SpChecksum> SpAsyncReader for SpChecksumTap<'_, R, C> {
+    async fn async_read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SpError> {
+        self.inner.async_read_bytes(buf).await?;
+        self.checksum.update(buf);
+        Ok(())
+    }
+
+    async fn async_read_some(&mut self, buf: &mut [u8]) -> Result<usize, SpError> {
+        let len = self.inner.async_read_some(buf).await?;
+        self.checksum.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<W: SpAsyncWriter + ?Sized, C: SpChecksum> SpAsyncWriter for SpChecksumTap<'_, W, C> {
+    async fn async_write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError> {
+        self.inner.async_write_bytes(buf).await?;
+        self.checksum.update(buf);
+        Ok(())
+    }
+}
+
+/// Computes the checksum of the bytes consumed from a slice since it was `start`
+#[doc(hidden)]
+pub fn slice_checksum<C: SpChecksum>(start: &[u8], rest: &[u8]) -> C::Output {
+    let mut checksum = C::default();
+    checksum.update(&start[..start.len() - rest.len()]);
+    checksum.finish()
+}
@@ -0,0 +1,269 @@
+use alloc::{collections::*, ffi::CString, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use core::hash::Hash;
+use core::{convert::TryFrom, mem::size_of};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+use crate::*;
+
+/// Reads the number of elements of a dynamically sized type
+async fn count_from_async_reader<R: SpAsyncReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+) -> Result<usize, crate::SpError> {
+    let len = match ctx.len.take() {
+        None => match ctx.len_type {
+            SpLenType::U8 => <u8>::inner_from_async_reader(src, ctx).await? as usize,
+            SpLenType::U16 => <u16>::inner_from_async_reader(src, ctx).await? as usize,
+            SpLenType::U32 => <u32>::inner_from_async_reader(src, ctx).await? as usize,
+            SpLenType::U64 => {
+                let v = <u64>::inner_from_async_reader(src, ctx).await?;
+                usize::try_from(v).map_err(|_| SpError::CountFieldOverflow)?
+            }
+            SpLenType::Varint => {
+                let v = <VarU64>::inner_from_async_reader(src, ctx).await?;
+                usize::try_from(v.0).map_err(|_| SpError::CountFieldOverflow)?
+            }
+        },
+        Some(v) => v,
+    };
+    ctx.check_count(len)?;
+    Ok(len)
+}
+
+impl SpAsyncRead for String {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        // Read the string as a Vec<u8>
+        let bytes = <Vec<u8>>::inner_from_async_reader(src, ctx).await?;
+
+        // Validate UTF8
+        match String::from_utf8(bytes) {
+            Ok(v) => Ok(v),
+            Err(_) => Err(SpError::InvalidBytes),
+        }
+    }
+}
+
+impl SpAsyncRead for CString {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        // Read one byte at a time adding them to bytes until we hit a null terminator
+        let mut bytes = Vec::new();
+        let mut tmp = [0u8];
+        loop {
+            ctx.check_bytes(1)?;
+            src.async_read_bytes(&mut tmp).await?;
+
+            #[cfg(feature = "verbose")]
+            ::log::debug!("  read(1)");
+
+            ctx.cursor += 1;
+
+            if tmp[0] == 0x00 {
+                break;
+            }
+            ctx.check_alloc(1)?;
+            bytes.push(tmp[0]);
+        }
+
+        unsafe { Ok(CString::from_vec_unchecked(bytes)) }
+    }
+}
+
+impl<T: SpAsyncRead> SpAsyncRead for Option<T> {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        // Read the u8 which indicates None or Some
+        if <u8>::inner_from_async_reader(src, ctx).await? == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(<T>::inner_from_async_reader(src, ctx).await?))
+        }
+    }
+}
+
+impl<const SIZE: usize, T: SpAsyncRead> SpAsyncRead for [T; SIZE] {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        let mut dst = MaybeUninit::<Self>::uninit();
+        // Convert our items into MaybeUninit<T>
+        let vals = unsafe {
+            core::slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut _ as *mut MaybeUninit<T>, SIZE)
+        };
+
+        if T::IS_SAFE_REPR {
+            // Convert our array into a mutable u8 slice
+            let dst_bytes = unsafe {
+                core::slice::from_raw_parts_mut(vals.as_mut_ptr() as *mut u8, size_of::<T>() * SIZE)
+            };
+
+            // Read all items into our allocation
+            ctx.check_bytes(dst_bytes.len())?;
+            src.async_read_bytes(dst_bytes).await?;
+            #[cfg(feature = "verbose")]
+            ::log::debug!("  read({})", size_of::<T>() * SIZE);
+
+            ctx.cursor += size_of::<T>() * SIZE;
+
+            // Validate every item's content
+            for v in vals.iter_mut() {
+                unsafe { <T>::validate_contents(ctx, v)? };
+            }
+        } else {
+            // Read & validate every item 1 by one
+            for v in vals.iter_mut() {
+                v.write(<T>::inner_from_async_reader(src, ctx).await?);
+            }
+        }
+
+        unsafe { Ok(dst.assume_init()) }
+    }
+}
+
+impl<T: SpAsyncRead> SpAsyncRead for Vec<T> {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        // Get the number of elements we must read
+        let len = count_from_async_reader(src, ctx).await?;
+        ctx.check_alloc(len.saturating_mul(size_of::<T>()))?;
+
+        // The maximum number of items we can pre-allocate to respect MAX_ALLOC_SIZE
+        let max_alloc_item_num: usize = core::cmp::max(MAX_ALLOC_SIZE / size_of::<T>().max(1), 1);
+
+        let mut r = Vec::<MaybeUninit<T>>::new();
+
+        if T::IS_SAFE_REPR {
+            let mut items_left = len;
+            while items_left > 0 {
+                let num_items = core::cmp::min(max_alloc_item_num, items_left);
+                // Make sure our allocation can accomodate an extra `num_items`
+                let old_len = r.len();
+                r.reserve(num_items);
+
+                // Cast our allocation into &mut [u8]
+                let dst_bytes = unsafe {
+                    core::slice::from_raw_parts_mut(
+                        r.as_mut_ptr().add(old_len) as *mut u8,
+                        num_items * size_of::<T>(),
+                    )
+                };
+
+                // Read `num_items` items into our allocation
+                ctx.check_bytes(dst_bytes.len())?;
+                src.async_read_bytes(dst_bytes).await?;
+                #[cfg(feature = "verbose")]
+                ::log::debug!("  read({})", num_items * size_of::<T>());
+
+                ctx.cursor += num_items * size_of::<T>();
+                unsafe {
+                    r.set_len(old_len + num_items);
+                }
+
+                // Validate every item's content
+                for v in r.iter_mut().skip(old_len) {
+                    unsafe { <T>::validate_contents(ctx, v)? };
+                }
+
+                items_left -= num_items;
+            }
+        } else {
+            for i in 0..len {
+                if i >= r.capacity() {
+                    r.reserve(core::cmp::min(max_alloc_item_num, len - i));
+                }
+                r.push(MaybeUninit::new(
+                    <T>::inner_from_async_reader(src, ctx).await?,
+                ));
+            }
+        }
+
+        unsafe { Ok(core::mem::transmute::<Vec<MaybeUninit<T>>, Vec<T>>(r)) }
+    }
+}
+
+impl<T: SpAsyncRead> SpAsyncRead for VecDeque<T> {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        // Use the Vec<T> implementation
+        Ok(VecDeque::from(
+            <Vec<T>>::inner_from_async_reader(src, ctx).await?,
+        ))
+    }
+}
+
+macro_rules! collection_async_read {
+    ($typ:ty, $add_func:ident, $generic:tt $(: $bound:ident $(+ $other:ident)*)? $(, $generics:tt $(: $bounds:ident $(+ $others:ident)*)?)*) => {
+        impl<$generic : SpAsyncRead $(+ $bound $(+ $other)*)? $(, $generics : SpAsyncRead $(+ $bounds$(+ $others)*)?)*> SpAsyncRead for $typ  {
+            async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+            ) -> Result<Self, crate::SpError>
+            {
+                // Get the number of elements we must read
+                let len = count_from_async_reader(src, ctx).await?;
+                ctx.check_alloc(len.saturating_mul(size_of::<$generic>() $(+ size_of::<$generics>())*))?;
+
+                let mut r = <$typ>::new();
+                for _i in 0..len {
+                    r.$add_func(
+                        <$generic>::inner_from_async_reader(src, ctx).await?
+                        $(,
+                            <$generics>::inner_from_async_reader(src, ctx).await?
+                        )*
+                    );
+                }
+
+                Ok(r)
+            }
+        }
+    };
+}
+collection_async_read!(LinkedList<T>, push_back, T);
+#[cfg(feature = "std")]
+collection_async_read!(HashSet<K>, insert, K: Eq + Hash);
+collection_async_read!(BTreeSet<K>, insert, K: Ord);
+#[cfg(feature = "std")]
+collection_async_read!(HashMap<K,V>, insert, K: Eq + Hash, V);
+collection_async_read!(BTreeMap<K,V>, insert, K: Ord, V);
+collection_async_read!(BinaryHeap<T>, push, T: Ord);
+
+macro_rules! tuple_async_read {
+    ($($name:ident : $idx:tt),+) => {
+        impl<$($name: SpAsyncRead),+> SpAsyncRead for ($($name,)+) {
+            async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+            ) -> Result<Self, crate::SpError> {
+                Ok(($(
+                    <$name>::inner_from_async_reader(src, ctx).await?,
+                )+))
+            }
+        }
+    };
+}
+tuple_async_read!(A: 0);
+tuple_async_read!(A: 0, B: 1);
+tuple_async_read!(A: 0, B: 1, C: 2);
+tuple_async_read!(A: 0, B: 1, C: 2, D: 3);
+tuple_async_read!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple_async_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+tuple_async_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+tuple_async_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+tuple_async_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+tuple_async_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+tuple_async_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+tuple_async_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
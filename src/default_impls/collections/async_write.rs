@@ -0,0 +1,223 @@
+use alloc::{collections::*, ffi::CString, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use core::hash::Hash;
+use core::{convert::TryFrom, ffi::CStr};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+use crate::*;
+
+/// Writes the number of elements of a dynamically sized type
+async fn count_to_async_writer<W: SpAsyncWriter + ?Sized>(
+    len: usize,
+    ctx: &mut SpCtx,
+    dst: &mut W,
+) -> Result<usize, crate::SpError> {
+    match ctx.len_type {
+        SpLenType::U8 => {
+            u8::try_from(len)
+                .map_err(|_| SpError::CountFieldOverflow)?
+                .inner_to_async_writer(ctx, dst)
+                .await
+        }
+        SpLenType::U16 => {
+            u16::try_from(len)
+                .map_err(|_| SpError::CountFieldOverflow)?
+                .inner_to_async_writer(ctx, dst)
+                .await
+        }
+        SpLenType::U32 => {
+            u32::try_from(len)
+                .map_err(|_| SpError::CountFieldOverflow)?
+                .inner_to_async_writer(ctx, dst)
+                .await
+        }
+        SpLenType::U64 => {
+            u64::try_from(len)
+                .map_err(|_| SpError::CountFieldOverflow)?
+                .inner_to_async_writer(ctx, dst)
+                .await
+        }
+        SpLenType::Varint => VarU64(len as u64).inner_to_async_writer(ctx, dst).await,
+    }
+}
+
+impl SpAsyncWrite for str {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.as_bytes().inner_to_async_writer(ctx, dst).await
+    }
+}
+
+impl SpAsyncWrite for &str {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.as_bytes().inner_to_async_writer(ctx, dst).await
+    }
+}
+
+impl SpAsyncWrite for String {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.as_str().inner_to_async_writer(ctx, dst).await
+    }
+}
+
+impl SpAsyncWrite for CStr {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.to_bytes_with_nul()
+            .inner_to_async_writer(ctx, dst)
+            .await
+    }
+}
+
+impl SpAsyncWrite for &CStr {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.to_bytes_with_nul()
+            .inner_to_async_writer(ctx, dst)
+            .await
+    }
+}
+
+impl SpAsyncWrite for CString {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.as_c_str().inner_to_async_writer(ctx, dst).await
+    }
+}
+
+impl<T: SpAsyncWrite> SpAsyncWrite for Option<T> {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        match self {
+            Some(v) => {
+                let o = 1u8;
+                Ok(o.inner_to_async_writer(ctx, dst).await?
+                    + v.inner_to_async_writer(ctx, dst).await?)
+            }
+            None => {
+                let o = 0u8;
+                o.inner_to_async_writer(ctx, dst).await
+            }
+        }
+    }
+}
+
+// Arrays have a static length, no count is written
+impl<const SIZE: usize, T: SpAsyncWrite> SpAsyncWrite for [T; SIZE] {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        let mut total_sz = 0;
+        for t in self.iter() {
+            total_sz += t.inner_to_async_writer(ctx, dst).await?;
+        }
+        Ok(total_sz)
+    }
+}
+
+macro_rules! iterator_async_write {
+    ($typ:ty $(, $generics:tt $(: $bound:ident $(+ $other:ident)*)?)*) => {
+        impl<$($generics : SpAsyncWrite $(+ $bound$(+ $other)*)?),*> SpAsyncWrite for $typ {
+            async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                let mut total_sz = 0;
+                // Write size if needed
+                if ctx.len.is_none() {
+                    total_sz += count_to_async_writer(self.len(), ctx, dst).await?;
+                }
+
+                // Dont propagate `len` field to inner types
+                ctx.len = None;
+
+                iterator_async_write!(inner, total_sz, self, ctx, dst $(+ $generics)*);
+
+                Ok(total_sz)
+            }
+        }
+    };
+    // Iterator with 1 element
+    (inner, $total_sz:ident, $self:ident, $ctx:ident, $dst: ident + $generic:tt) => {
+        for t1 in $self.iter() {
+            $total_sz += t1.inner_to_async_writer($ctx, $dst).await?;
+        }
+    };
+    // Iterator with 2 elements
+    (inner, $total_sz:ident, $self:ident, $ctx:ident, $dst: ident + $generic1:tt + $generic2:tt) => {
+        for (t1, t2) in $self.iter() {
+            $total_sz += t1.inner_to_async_writer($ctx, $dst).await?;
+            $total_sz += t2.inner_to_async_writer($ctx, $dst).await?;
+        }
+    };
+}
+
+iterator_async_write!([T], T);
+iterator_async_write!(&[T], T);
+iterator_async_write!(Vec<T>, T);
+iterator_async_write!(VecDeque<T>, T);
+iterator_async_write!(LinkedList<T>, T);
+#[cfg(feature = "std")]
+iterator_async_write!(HashSet<K>, K: Eq + Hash);
+iterator_async_write!(BTreeSet<K>, K: Ord);
+#[cfg(feature = "std")]
+iterator_async_write!(HashMap<K,V>, K: Eq + Hash, V);
+iterator_async_write!(BTreeMap<K,V>, K: Ord, V);
+iterator_async_write!(BinaryHeap<T>, T: Ord);
+
+macro_rules! tuple_async_write {
+    ($($name:ident : $idx:tt),+) => {
+        impl<$($name: SpAsyncWrite),+> SpAsyncWrite for ($($name,)+) {
+            async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                let mut total_sz = 0;
+                $(
+                    total_sz += self.$idx.inner_to_async_writer(ctx, dst).await?;
+                )+
+                Ok(total_sz)
+            }
+        }
+    };
+}
+tuple_async_write!(A: 0);
+tuple_async_write!(A: 0, B: 1);
+tuple_async_write!(A: 0, B: 1, C: 2);
+tuple_async_write!(A: 0, B: 1, C: 2, D: 3);
+tuple_async_write!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple_async_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+tuple_async_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+tuple_async_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+tuple_async_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+tuple_async_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+tuple_async_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+tuple_async_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
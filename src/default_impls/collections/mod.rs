@@ -3,6 +3,10 @@
  * Option<T>, Vec<T>, etc...
 */
 mod read;
+mod read_raw;
 mod write;
-pub use read::*;
-pub use write::*;
+
+#[cfg(feature = "async")]
+mod async_read;
+#[cfg(feature = "async")]
+mod async_write;
@@ -1,10 +1,45 @@
+use alloc::{collections::*, ffi::CString, string::String, vec::Vec};
+#[cfg(feature = "std")]
 use core::hash::Hash;
-use std::{collections::*, ffi::CString, mem::size_of};
+use core::{convert::TryFrom, mem::size_of};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
 
 use crate::*;
 
+/// Reads the number of elements of a dynamically sized type
+fn count_from_reader<R: SpReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+) -> Result<usize, crate::SpError> {
+    let len = match ctx.len.take() {
+        None => match ctx.len_type {
+            SpLenType::U8 => {
+                *<u8>::inner_from_reader(src, ctx, &mut MaybeUninit::uninit())? as usize
+            }
+            SpLenType::U16 => {
+                *<u16>::inner_from_reader(src, ctx, &mut MaybeUninit::uninit())? as usize
+            }
+            SpLenType::U32 => {
+                *<u32>::inner_from_reader(src, ctx, &mut MaybeUninit::uninit())? as usize
+            }
+            SpLenType::U64 => {
+                let v = *<u64>::inner_from_reader(src, ctx, &mut MaybeUninit::uninit())?;
+                usize::try_from(v).map_err(|_| SpError::CountFieldOverflow)?
+            }
+            SpLenType::Varint => {
+                let v = *<VarU64>::inner_from_reader(src, ctx, &mut MaybeUninit::uninit())?;
+                usize::try_from(v.0).map_err(|_| SpError::CountFieldOverflow)?
+            }
+        },
+        Some(v) => v,
+    };
+    ctx.check_count(len)?;
+    Ok(len)
+}
+
 impl SpRead for String {
-    fn inner_from_reader<'a, R: Read + ?Sized>(
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
         src: &mut R,
         ctx: &mut SpCtx,
         dst: &'a mut MaybeUninit<Self>,
@@ -34,7 +69,7 @@ impl SpRead for String {
 }
 
 impl SpRead for CString {
-    fn inner_from_reader<'a, R: Read + ?Sized>(
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
         src: &mut R,
         ctx: &mut SpCtx,
         dst: &'a mut MaybeUninit<Self>,
@@ -43,21 +78,21 @@ impl SpRead for CString {
         let mut bytes = Vec::new();
         let mut tmp = [0u8];
         loop {
-            if let Err(e) = src.read_exact(&mut tmp) {
-                return Err(SpError::ReadFailed(e));
-            }
+            ctx.check_bytes(1)?;
+            src.read_bytes(&mut tmp)?;
 
             #[cfg(feature = "verbose")]
             ::log::debug!("  read(1)");
 
+            ctx.cursor += 1;
+
             if tmp[0] == 0x00 {
                 break;
             }
+            ctx.check_alloc(1)?;
             bytes.push(tmp[0]);
         }
 
-        ctx.cursor += bytes.len();
-
         unsafe {
             dst.write(CString::from_vec_unchecked(bytes));
 
@@ -67,7 +102,7 @@ impl SpRead for CString {
 }
 
 impl<T: SpRead> SpRead for Option<T> {
-    fn inner_from_reader<'a, R: Read + ?Sized>(
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
         src: &mut R,
         ctx: &mut SpCtx,
         dst: &'a mut MaybeUninit<Self>,
@@ -90,7 +125,7 @@ impl<T: SpRead> SpRead for Option<T> {
 }
 
 impl<const SIZE: usize, T: SpRead> SpRead for [T; SIZE] {
-    fn inner_from_reader<'a, R: Read + ?Sized>(
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
         src: &mut R,
         ctx: &mut SpCtx,
         dst: &'a mut MaybeUninit<Self>,
@@ -103,9 +138,8 @@ impl<const SIZE: usize, T: SpRead> SpRead for [T; SIZE] {
             };
 
             // Read all items into our allocation
-            if let Err(e) = src.read_exact(dst_bytes) {
-                return Err(SpError::ReadFailed(e));
-            }
+            ctx.check_bytes(dst_bytes.len())?;
+            src.read_bytes(dst_bytes)?;
             #[cfg(feature = "verbose")]
             ::log::debug!("  read({})", dst_bytes.len());
             
@@ -142,46 +176,37 @@ impl<const SIZE: usize, T: SpRead> SpRead for [T; SIZE] {
 }
 
 impl<T: SpRead> SpRead for Vec<T> {
-    fn inner_from_reader<'a, R: Read + ?Sized>(
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
         src: &mut R,
         ctx: &mut SpCtx,
         dst: &'a mut MaybeUninit<Self>,
     ) -> Result<&'a mut Self, crate::SpError> {
         // Get the number of elements we must read
-        let len = match ctx.len.take() {
-            None => {
-                let mut tmp = MaybeUninit::uninit();
-                *<DefaultCountType>::inner_from_reader(src, ctx, &mut tmp)? as usize
-            }
-            Some(v) => v,
-        };
+        let len = count_from_reader(src, ctx)?;
+        ctx.check_alloc(len.saturating_mul(size_of::<T>()))?;
 
         // The maximum number of items we can pre-allocate to respect MAX_ALLOC_SIZE
-        let max_alloc_item_num: usize = std::cmp::max(MAX_ALLOC_SIZE / size_of::<T>(), 1);
+        let max_alloc_item_num: usize = core::cmp::max(MAX_ALLOC_SIZE / size_of::<T>().max(1), 1);
 
         let mut r = Vec::<MaybeUninit<T>>::new();
 
         if T::IS_SAFE_REPR {
             let mut items_left = len;
             while items_left > 0 {
-                let num_items = std::cmp::min(max_alloc_item_num, items_left);
+                let num_items = core::cmp::min(max_alloc_item_num, items_left);
                 // Make sure our allocation can accomodate an extra `num_items`
                 let old_len = r.len();
                 r.reserve(num_items);
 
                 // Cast our allocation into &mut [u8]
-                let next_free_ptr = unsafe { r.get_unchecked_mut(old_len) } as *mut _ as *mut u8;
+                let next_free_ptr = unsafe { r.as_mut_ptr().add(old_len) } as *mut u8;
                 let dst_bytes = unsafe {
-                    core::slice::from_raw_parts_mut(
-                        next_free_ptr as *mut u8,
-                        num_items * size_of::<T>(),
-                    )
+                    core::slice::from_raw_parts_mut(next_free_ptr, num_items * size_of::<T>())
                 };
 
                 // Read `num_items` items into our allocation
-                if let Err(e) = src.read_exact(dst_bytes) {
-                    return Err(SpError::ReadFailed(e));
-                }
+                ctx.check_bytes(dst_bytes.len())?;
+                src.read_bytes(dst_bytes)?;
                 #[cfg(feature = "verbose")]
                 ::log::debug!("  read({})", dst_bytes.len());
 
@@ -200,7 +225,7 @@ impl<T: SpRead> SpRead for Vec<T> {
         } else {
             for i in 0..len {
                 if i >= r.capacity() {
-                    r.reserve(std::cmp::min(max_alloc_item_num, len - i));
+                    r.reserve(core::cmp::min(max_alloc_item_num, len - i));
                 }
                 r.push({
                     let mut v = MaybeUninit::uninit();
@@ -211,14 +236,14 @@ impl<T: SpRead> SpRead for Vec<T> {
         }
 
         unsafe {
-            dst.write(core::mem::transmute(r));
+            dst.write(core::mem::transmute::<Vec<MaybeUninit<T>>, Vec<T>>(r));
             Ok(dst.assume_init_mut())
         }
     }
 }
 
 impl<T: SpRead> SpRead for VecDeque<T> {
-    fn inner_from_reader<'a, R: Read + ?Sized>(
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
         src: &mut R,
         ctx: &mut SpCtx,
         dst: &'a mut MaybeUninit<Self>,
@@ -237,20 +262,15 @@ impl<T: SpRead> SpRead for VecDeque<T> {
 macro_rules! collection_read {
     ($typ:ty, $add_func:ident, $generic:tt $(: $bound:ident $(+ $other:ident)*)? $(, $generics:tt $(: $bounds:ident $(+ $others:ident)*)?)*) => {
         impl<'b, $generic : SpRead $(+ $bound $(+ $other)*)? $(, $generics : SpRead $(+ $bounds$(+ $others)*)?)*> SpRead for $typ  {
-            fn inner_from_reader<'a, R: Read + ?Sized>(
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
                 src: &mut R,
                 ctx: &mut SpCtx,
                 dst: &'a mut MaybeUninit<Self>,
             ) -> Result<&'a mut Self, crate::SpError>
             {
                 // Get the number of elements we must read
-                let len = match ctx.len.take() {
-                    None => {
-                        let mut tmp = MaybeUninit::uninit();
-                        *<DefaultCountType>::inner_from_reader(src, ctx, &mut tmp)? as usize
-                    },
-                    Some(v) => v,
-                };
+                let len = count_from_reader(src, ctx)?;
+                ctx.check_alloc(len.saturating_mul(size_of::<$generic>() $(+ size_of::<$generics>())*))?;
 
                 let mut r = <$typ>::new();
                 for _i in 0..len {
@@ -277,8 +297,65 @@ macro_rules! collection_read {
     };
 }
 collection_read!(LinkedList<T>, push_back, T);
+#[cfg(feature = "std")]
 collection_read!(HashSet<K>, insert, K: Eq + Hash);
 collection_read!(BTreeSet<K>, insert, K: Ord);
+#[cfg(feature = "std")]
 collection_read!(HashMap<K,V>, insert, K: Eq + Hash, V);
 collection_read!(BTreeMap<K,V>, insert, K: Ord, V);
 collection_read!(BinaryHeap<T>, push, T: Ord);
+
+macro_rules! tuple_read {
+    ($($name:ident : $idx:tt),+) => {
+        impl<$($name: SpRead),+> SpRead for ($($name,)+) {
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                let p = dst.as_mut_ptr();
+
+                if true $(&& <$name>::IS_SAFE_REPR)+ {
+                    // The elements are not laid out in order in memory.
+                    // Read all of their bytes at once into a buffer and parse them from there
+                    let num_bytes = 0 $(+ size_of::<$name>())+;
+                    ctx.check_bytes(num_bytes)?;
+
+                    // Self is always big enough to hold every element
+                    let mut buf = MaybeUninit::<Self>::zeroed();
+                    let bytes = unsafe {
+                        core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, num_bytes)
+                    };
+                    src.read_bytes(bytes)?;
+
+                    let mut bytes: &[u8] = bytes;
+                    $(
+                        <$name>::inner_from_reader(&mut bytes, ctx, unsafe {
+                            &mut *(core::ptr::addr_of_mut!((*p).$idx) as *mut MaybeUninit<$name>)
+                        })?;
+                    )+
+                } else {
+                    $(
+                        <$name>::inner_from_reader(src, ctx, unsafe {
+                            &mut *(core::ptr::addr_of_mut!((*p).$idx) as *mut MaybeUninit<$name>)
+                        })?;
+                    )+
+                }
+
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+tuple_read!(A: 0);
+tuple_read!(A: 0, B: 1);
+tuple_read!(A: 0, B: 1, C: 2);
+tuple_read!(A: 0, B: 1, C: 2, D: 3);
+tuple_read!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+tuple_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+tuple_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+tuple_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+tuple_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+tuple_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+tuple_read!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
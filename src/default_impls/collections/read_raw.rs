@@ -0,0 +1,330 @@
+use alloc::{collections::*, ffi::CString, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use core::hash::Hash;
+use core::{
+    convert::TryFrom,
+    ffi::CStr,
+    mem::{align_of, size_of},
+};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+
+use crate::*;
+
+/// Reads the number of elements of a dynamically sized type
+fn count_from_slice(src: &mut &[u8], ctx: &mut SpCtx) -> Result<usize, crate::SpError> {
+    let len = match ctx.len.take() {
+        None => match ctx.len_type {
+            SpLenType::U8 => {
+                *<u8>::inner_from_slice(src, ctx, &mut MaybeUninit::uninit())? as usize
+            }
+            SpLenType::U16 => {
+                *<u16>::inner_from_slice(src, ctx, &mut MaybeUninit::uninit())? as usize
+            }
+            SpLenType::U32 => {
+                *<u32>::inner_from_slice(src, ctx, &mut MaybeUninit::uninit())? as usize
+            }
+            SpLenType::U64 => {
+                let v = *<u64>::inner_from_slice(src, ctx, &mut MaybeUninit::uninit())?;
+                usize::try_from(v).map_err(|_| SpError::CountFieldOverflow)?
+            }
+            SpLenType::Varint => {
+                let v = *<VarU64>::inner_from_slice(src, ctx, &mut MaybeUninit::uninit())?;
+                usize::try_from(v.0).map_err(|_| SpError::CountFieldOverflow)?
+            }
+        },
+        Some(v) => v,
+    };
+    ctx.check_count(len)?;
+    Ok(len)
+}
+
+macro_rules! slice_read_raw {
+    ($($typ:ty),*) => {
+        $(
+        /// Borrows the elements directly from the input.
+        ///
+        /// The input must be properly aligned for the element type and be in the native endianness.
+        impl<'b: 'x, 'x> SpReadRaw<'b> for &'x [$typ] {
+            fn inner_from_slice<'a>(
+                src: &mut &'b [u8],
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                let len = count_from_slice(src, ctx)?;
+
+                // A view cannot swap the bytes in place
+                if size_of::<$typ>() > 1 && ctx.is_little_endian != cfg!(target_endian = "little") {
+                    return Err(SpError::InvalidBytes);
+                }
+                // Make sure the items can be referenced in place
+                if src.as_ptr() as usize % align_of::<$typ>() != 0 {
+                    return Err(SpError::BadAlignment);
+                }
+
+                let num_bytes = match len.checked_mul(size_of::<$typ>()) {
+                    Some(v) => v,
+                    None => return Err(SpError::NotEnoughSpace),
+                };
+                let bytes = bytes_from_slice(src, ctx, num_bytes)?;
+
+                dst.write(unsafe {
+                    core::slice::from_raw_parts(bytes.as_ptr() as *const $typ, len)
+                });
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+        )*
+    };
+}
+
+// Only types where every bit pattern is valid can be borrowed without checking each item
+slice_read_raw!(u8, u16, u32, u64, u128, usize);
+slice_read_raw!(i8, i16, i32, i64, i128, isize);
+slice_read_raw!(f32, f64);
+
+impl<'b: 'x, 'x> SpReadRaw<'b> for &'x str {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let len = count_from_slice(src, ctx)?;
+        let bytes = bytes_from_slice(src, ctx, len)?;
+
+        // Validate UTF8
+        match core::str::from_utf8(bytes) {
+            Ok(v) => dst.write(v),
+            Err(_) => return Err(SpError::InvalidBytes),
+        };
+
+        let v = unsafe { dst.assume_init_mut() };
+
+        #[cfg(feature = "verbose")]
+        ::log::debug!("'{}'", v);
+
+        Ok(v)
+    }
+}
+
+impl<'b: 'x, 'x> SpReadRaw<'b> for &'x CStr {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        // Find the null terminator
+        let len = match src.iter().position(|b| *b == 0x00) {
+            Some(idx) => idx + 1,
+            None => return Err(SpError::NotEnoughSpace),
+        };
+        let bytes = bytes_from_slice(src, ctx, len)?;
+
+        dst.write(unsafe { CStr::from_bytes_with_nul_unchecked(bytes) });
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
+
+impl<'b> SpReadRaw<'b> for String {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut tmp = MaybeUninit::uninit();
+        let s: &str = <&str>::inner_from_slice(src, ctx, &mut tmp)?;
+        ctx.check_alloc(s.len())?;
+
+        dst.write(String::from(s));
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
+
+impl<'b> SpReadRaw<'b> for CString {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut tmp = MaybeUninit::uninit();
+        let s: &CStr = <&CStr>::inner_from_slice(src, ctx, &mut tmp)?;
+        ctx.check_alloc(s.to_bytes().len())?;
+
+        dst.write(CString::from(s));
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
+
+impl<'b, T: SpReadRaw<'b>> SpReadRaw<'b> for Option<T> {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        // Read the u8 which indicates None or Some
+        let mut tmp = MaybeUninit::uninit();
+        let v = <u8>::inner_from_slice(src, ctx, &mut tmp)?;
+
+        // Initialize the value
+        if *v == 0 {
+            dst.write(None);
+        } else {
+            let mut tmp = MaybeUninit::uninit();
+            <T>::inner_from_slice(src, ctx, &mut tmp)?;
+            dst.write(Some(unsafe { tmp.assume_init() }));
+        }
+
+        unsafe { Ok(dst.assume_init_mut()) }
+    }
+}
+
+impl<'b, const SIZE: usize, T: SpReadRaw<'b>> SpReadRaw<'b> for [T; SIZE] {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        // Convert our items into MaybeUninit<T>
+        let vals = unsafe {
+            core::slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut _ as *mut MaybeUninit<T>, SIZE)
+        };
+
+        // Read & validate every item 1 by one
+        for v in vals.iter_mut() {
+            <T>::inner_from_slice(src, ctx, v)?;
+        }
+
+        unsafe { Ok(dst.assume_init_mut()) }
+    }
+}
+
+impl<'b, T: SpReadRaw<'b>> SpReadRaw<'b> for Vec<T> {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        // Get the number of elements we must read
+        let len = count_from_slice(src, ctx)?;
+        ctx.check_alloc(len.saturating_mul(size_of::<T>()))?;
+
+        // Make sure the input could contain every item before allocating
+        if T::IS_SAFE_REPR && len.saturating_mul(size_of::<T>()) > src.len() {
+            return Err(SpError::NotEnoughSpace);
+        }
+
+        // The maximum number of items we can pre-allocate to respect MAX_ALLOC_SIZE
+        let max_alloc_item_num: usize = core::cmp::max(MAX_ALLOC_SIZE / size_of::<T>().max(1), 1);
+
+        let mut r = Vec::<T>::new();
+        for i in 0..len {
+            if i >= r.capacity() {
+                r.reserve(core::cmp::min(max_alloc_item_num, len - i));
+            }
+            r.push({
+                let mut v = MaybeUninit::uninit();
+                <T>::inner_from_slice(src, ctx, &mut v)?;
+                unsafe { v.assume_init() }
+            });
+        }
+
+        dst.write(r);
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
+
+impl<'b, T: SpReadRaw<'b>> SpReadRaw<'b> for VecDeque<T> {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        // Use the Vec<T> implementation
+        let mut tmp = MaybeUninit::uninit();
+        <Vec<T>>::inner_from_slice(src, ctx, &mut tmp)?;
+
+        unsafe {
+            dst.write(VecDeque::from(tmp.assume_init()));
+            Ok(dst.assume_init_mut())
+        }
+    }
+}
+
+macro_rules! collection_read_raw {
+    ($typ:ty, $add_func:ident, $generic:tt $(: $bound:ident $(+ $other:ident)*)? $(, $generics:tt $(: $bounds:ident $(+ $others:ident)*)?)*) => {
+        impl<'b, $generic : SpReadRaw<'b> $(+ $bound $(+ $other)*)? $(, $generics : SpReadRaw<'b> $(+ $bounds$(+ $others)*)?)*> SpReadRaw<'b> for $typ  {
+            fn inner_from_slice<'a>(
+                src: &mut &'b [u8],
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError>
+            {
+                // Get the number of elements we must read
+                let len = count_from_slice(src, ctx)?;
+                ctx.check_alloc(len.saturating_mul(size_of::<$generic>() $(+ size_of::<$generics>())*))?;
+
+                let mut r = <$typ>::new();
+                for _i in 0..len {
+                    r.$add_func(
+                        {
+                            let mut v = MaybeUninit::<$generic>::uninit();
+                            <$generic>::inner_from_slice(src, ctx, &mut v)?;
+                            unsafe { v.assume_init() }
+                        }
+                        $(,
+                            {
+                                let mut v = MaybeUninit::<$generics>::uninit();
+                                <$generics>::inner_from_slice(src, ctx, &mut v)?;
+                                unsafe { v.assume_init() }
+                            }
+                        )*
+                    );
+                }
+
+                dst.write(r);
+                Ok(unsafe{ dst.assume_init_mut()})
+            }
+        }
+    };
+}
+collection_read_raw!(LinkedList<T>, push_back, T);
+#[cfg(feature = "std")]
+collection_read_raw!(HashSet<K>, insert, K: Eq + Hash);
+collection_read_raw!(BTreeSet<K>, insert, K: Ord);
+#[cfg(feature = "std")]
+collection_read_raw!(HashMap<K,V>, insert, K: Eq + Hash, V);
+collection_read_raw!(BTreeMap<K,V>, insert, K: Ord, V);
+collection_read_raw!(BinaryHeap<T>, push, T: Ord);
+
+macro_rules! tuple_read_raw {
+    ($($name:ident : $idx:tt),+) => {
+        impl<'b, $($name: SpReadRaw<'b>),+> SpReadRaw<'b> for ($($name,)+) {
+            fn inner_from_slice<'a>(
+                src: &mut &'b [u8],
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                let p = dst.as_mut_ptr();
+                $(
+                    <$name>::inner_from_slice(src, ctx, unsafe {
+                        &mut *(core::ptr::addr_of_mut!((*p).$idx) as *mut MaybeUninit<$name>)
+                    })?;
+                )+
+
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+tuple_read_raw!(A: 0);
+tuple_read_raw!(A: 0, B: 1);
+tuple_read_raw!(A: 0, B: 1, C: 2);
+tuple_read_raw!(A: 0, B: 1, C: 2, D: 3);
+tuple_read_raw!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple_read_raw!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+tuple_read_raw!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+tuple_read_raw!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+tuple_read_raw!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+tuple_read_raw!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+tuple_read_raw!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+tuple_read_raw!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
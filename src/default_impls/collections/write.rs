@@ -1,15 +1,47 @@
-use std::{
-    collections::*,
-    convert::TryInto,
-    ffi::{CStr, CString},
-};
-
+use alloc::{collections::*, ffi::CString, string::String, vec::Vec};
+#[cfg(feature = "std")]
 use core::hash::Hash;
+use core::{convert::TryFrom, ffi::CStr};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
 
 use crate::*;
 
+/// Writes the number of elements of a dynamically sized type
+fn count_to_writer<W: SpWriter + ?Sized>(
+    len: usize,
+    ctx: &mut SpCtx,
+    dst: &mut W,
+) -> Result<usize, crate::SpError> {
+    match ctx.len_type {
+        SpLenType::U8 => u8::try_from(len)
+            .map_err(|_| SpError::CountFieldOverflow)?
+            .inner_to_writer(ctx, dst),
+        SpLenType::U16 => u16::try_from(len)
+            .map_err(|_| SpError::CountFieldOverflow)?
+            .inner_to_writer(ctx, dst),
+        SpLenType::U32 => u32::try_from(len)
+            .map_err(|_| SpError::CountFieldOverflow)?
+            .inner_to_writer(ctx, dst),
+        SpLenType::U64 => u64::try_from(len)
+            .map_err(|_| SpError::CountFieldOverflow)?
+            .inner_to_writer(ctx, dst),
+        SpLenType::Varint => VarU64(len as u64).inner_to_writer(ctx, dst),
+    }
+}
+
+impl SpWrite for str {
+    fn inner_to_writer<W: SpWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.as_bytes().inner_to_writer(ctx, dst)
+    }
+}
+
 impl SpWrite for &str {
-    fn inner_to_writer<W: Write + ?Sized>(
+    fn inner_to_writer<W: SpWriter + ?Sized>(
         &self,
         ctx: &mut SpCtx,
         dst: &mut W,
@@ -19,7 +51,7 @@ impl SpWrite for &str {
 }
 
 impl SpWrite for String {
-    fn inner_to_writer<W: Write + ?Sized>(
+    fn inner_to_writer<W: SpWriter + ?Sized>(
         &self,
         ctx: &mut SpCtx,
         dst: &mut W,
@@ -28,8 +60,18 @@ impl SpWrite for String {
     }
 }
 
+impl SpWrite for CStr {
+    fn inner_to_writer<W: SpWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.to_bytes_with_nul().inner_to_writer(ctx, dst)
+    }
+}
+
 impl SpWrite for &CStr {
-    fn inner_to_writer<W: Write + ?Sized>(
+    fn inner_to_writer<W: SpWriter + ?Sized>(
         &self,
         ctx: &mut SpCtx,
         dst: &mut W,
@@ -39,7 +81,7 @@ impl SpWrite for &CStr {
 }
 
 impl SpWrite for CString {
-    fn inner_to_writer<W: Write + ?Sized>(
+    fn inner_to_writer<W: SpWriter + ?Sized>(
         &self,
         ctx: &mut SpCtx,
         dst: &mut W,
@@ -49,7 +91,7 @@ impl SpWrite for CString {
 }
 
 impl<T: SpWrite> SpWrite for Option<T> {
-    fn inner_to_writer<W: Write + ?Sized>(
+    fn inner_to_writer<W: SpWriter + ?Sized>(
         &self,
         ctx: &mut SpCtx,
         dst: &mut W,
@@ -67,10 +109,25 @@ impl<T: SpWrite> SpWrite for Option<T> {
     }
 }
 
+// Arrays have a static length, no count is written
+impl<const SIZE: usize, T: SpWrite> SpWrite for [T; SIZE] {
+    fn inner_to_writer<W: SpWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        let mut total_sz = 0;
+        for t in self.iter() {
+            total_sz += t.inner_to_writer(ctx, dst)?;
+        }
+        Ok(total_sz)
+    }
+}
+
 macro_rules! iterator_write {
     ($typ:ty $(, $generics:tt $(: $bound:ident $(+ $other:ident)*)?)*) => {
         impl<$($generics : SpWrite $(+ $bound$(+ $other)*)?),*> SpWrite for $typ {
-            fn inner_to_writer<W: Write + ?Sized>(
+            fn inner_to_writer<W: SpWriter + ?Sized>(
                 &self,
                 ctx: &mut SpCtx,
                 dst: &mut W,
@@ -78,11 +135,7 @@ macro_rules! iterator_write {
                 let mut total_sz = 0;
                 // Write size if needed
                 if ctx.len.is_none() {
-                    let len: DefaultCountType = match self.len().try_into() {
-                        Ok(v) => v,
-                        Err(_e) => return Err(SpError::CountFieldOverflow),
-                    };
-                    total_sz += len.inner_to_writer(ctx, dst)?;
+                    total_sz += count_to_writer(self.len(), ctx, dst)?;
                 }
 
                 // Dont propagate `len` field to inner types
@@ -109,12 +162,45 @@ macro_rules! iterator_write {
     };
 }
 
+iterator_write!([T], T);
 iterator_write!(&[T], T);
 iterator_write!(Vec<T>, T);
 iterator_write!(VecDeque<T>, T);
 iterator_write!(LinkedList<T>, T);
+#[cfg(feature = "std")]
 iterator_write!(HashSet<K>, K: Eq + Hash);
 iterator_write!(BTreeSet<K>, K: Ord);
+#[cfg(feature = "std")]
 iterator_write!(HashMap<K,V>, K: Eq + Hash, V);
 iterator_write!(BTreeMap<K,V>, K: Ord, V);
 iterator_write!(BinaryHeap<T>, T: Ord);
+
+macro_rules! tuple_write {
+    ($($name:ident : $idx:tt),+) => {
+        impl<$($name: SpWrite),+> SpWrite for ($($name,)+) {
+            fn inner_to_writer<W: SpWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                let mut total_sz = 0;
+                $(
+                    total_sz += self.$idx.inner_to_writer(ctx, dst)?;
+                )+
+                Ok(total_sz)
+            }
+        }
+    };
+}
+tuple_write!(A: 0);
+tuple_write!(A: 0, B: 1);
+tuple_write!(A: 0, B: 1, C: 2);
+tuple_write!(A: 0, B: 1, C: 2, D: 3);
+tuple_write!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+tuple_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+tuple_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+tuple_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+tuple_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+tuple_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+tuple_write!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
@@ -1,5 +1,5 @@
-mod primitive;
-pub use primitive::*;
-
 mod collections;
-pub use collections::*;
+mod pointer;
+mod primitive;
+mod value;
+mod varint;
@@ -0,0 +1,73 @@
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    rc::Rc,
+    string::String,
+    vec::Vec,
+};
+use core::mem::size_of;
+
+use crate::*;
+
+macro_rules! ptr_async_read {
+    ($ptr:ident) => {
+        impl<T: SpAsyncRead> SpAsyncRead for $ptr<T> {
+            async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+            ) -> Result<Self, crate::SpError> {
+                ctx.check_alloc(size_of::<T>())?;
+
+                // The future is boxed to allow recursive types
+                let v = Box::pin(<T>::inner_from_async_reader(src, ctx)).await?;
+                Ok($ptr::new(v))
+            }
+        }
+
+        impl<T: SpAsyncRead> SpAsyncRead for $ptr<[T]> {
+            async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+            ) -> Result<Self, crate::SpError> {
+                // Use the Vec<T> implementation
+                Ok($ptr::from(
+                    <Vec<T>>::inner_from_async_reader(src, ctx).await?,
+                ))
+            }
+        }
+
+        impl SpAsyncRead for $ptr<str> {
+            async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+            ) -> Result<Self, crate::SpError> {
+                // Use the String implementation
+                Ok($ptr::from(
+                    <String>::inner_from_async_reader(src, ctx).await?,
+                ))
+            }
+        }
+    };
+}
+
+ptr_async_read!(Box);
+ptr_async_read!(Rc);
+#[cfg(target_has_atomic = "ptr")]
+ptr_async_read!(Arc);
+
+/// Always reads into [Cow::Owned]
+impl<'x, T: ToOwned + ?Sized> SpAsyncRead for Cow<'x, T>
+where
+    T::Owned: SpAsyncRead,
+{
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        Ok(Cow::Owned(
+            <T::Owned>::inner_from_async_reader(src, ctx).await?,
+        ))
+    }
+}
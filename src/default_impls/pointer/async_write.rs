@@ -0,0 +1,39 @@
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    rc::Rc,
+};
+
+use crate::*;
+
+macro_rules! ptr_async_write {
+    ($ptr:ident) => {
+        impl<T: SpAsyncWrite + ?Sized> SpAsyncWrite for $ptr<T> {
+            async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                // The future is boxed to allow recursive types
+                Box::pin((**self).inner_to_async_writer(ctx, dst)).await
+            }
+        }
+    };
+}
+
+ptr_async_write!(Box);
+ptr_async_write!(Rc);
+#[cfg(target_has_atomic = "ptr")]
+ptr_async_write!(Arc);
+
+impl<'x, T: SpAsyncWrite + ToOwned + ?Sized> SpAsyncWrite for Cow<'x, T> {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        (**self).inner_to_async_writer(ctx, dst).await
+    }
+}
@@ -0,0 +1,12 @@
+/** Implements simple_parse traits on smart pointers such as :
+ * Box<T>, Rc<T>, Arc<T>
+ * Box<[T]>, Arc<str>, Cow<'_, T>, etc...
+*/
+mod read;
+mod read_raw;
+mod write;
+
+#[cfg(feature = "async")]
+mod async_read;
+#[cfg(feature = "async")]
+mod async_write;
@@ -0,0 +1,112 @@
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    rc::Rc,
+    string::String,
+    vec::Vec,
+};
+use core::mem::size_of;
+
+use crate::*;
+
+impl<T: SpRead> SpRead for Box<T> {
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        ctx.check_alloc(size_of::<T>())?;
+
+        // Read directly into the allocation
+        let mut b = Box::<T>::new_uninit();
+        <T>::inner_from_reader(src, ctx, &mut b)?;
+
+        dst.write(unsafe { b.assume_init() });
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
+
+macro_rules! shared_read {
+    ($ptr:ident) => {
+        impl<T: SpRead> SpRead for $ptr<T> {
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                ctx.check_alloc(size_of::<T>())?;
+
+                // Read directly into the allocation which cannot be shared yet
+                let mut p = $ptr::<T>::new_uninit();
+                let v = unsafe { $ptr::get_mut(&mut p).unwrap_unchecked() };
+                <T>::inner_from_reader(src, ctx, v)?;
+
+                dst.write(unsafe { p.assume_init() });
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+
+shared_read!(Rc);
+#[cfg(target_has_atomic = "ptr")]
+shared_read!(Arc);
+
+macro_rules! slice_ptr_read {
+    ($ptr:ident) => {
+        impl<T: SpRead> SpRead for $ptr<[T]> {
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                // Use the Vec<T> implementation
+                let mut tmp = MaybeUninit::uninit();
+                <Vec<T>>::inner_from_reader(src, ctx, &mut tmp)?;
+
+                dst.write($ptr::from(unsafe { tmp.assume_init() }));
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+
+        impl SpRead for $ptr<str> {
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                // Use the String implementation
+                let mut tmp = MaybeUninit::uninit();
+                <String>::inner_from_reader(src, ctx, &mut tmp)?;
+
+                dst.write($ptr::from(unsafe { tmp.assume_init() }));
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+
+slice_ptr_read!(Box);
+slice_ptr_read!(Rc);
+#[cfg(target_has_atomic = "ptr")]
+slice_ptr_read!(Arc);
+
+/// Always reads into [Cow::Owned]
+impl<'x, T: ToOwned + ?Sized> SpRead for Cow<'x, T>
+where
+    T::Owned: SpRead,
+{
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut tmp = MaybeUninit::uninit();
+        <T::Owned>::inner_from_reader(src, ctx, &mut tmp)?;
+
+        dst.write(Cow::Owned(unsafe { tmp.assume_init() }));
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
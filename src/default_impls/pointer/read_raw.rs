@@ -0,0 +1,112 @@
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    rc::Rc,
+    string::String,
+    vec::Vec,
+};
+use core::mem::size_of;
+
+use crate::*;
+
+impl<'b, T: SpReadRaw<'b>> SpReadRaw<'b> for Box<T> {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        ctx.check_alloc(size_of::<T>())?;
+
+        // Read directly into the allocation
+        let mut b = Box::<T>::new_uninit();
+        <T>::inner_from_slice(src, ctx, &mut b)?;
+
+        dst.write(unsafe { b.assume_init() });
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
+
+macro_rules! shared_read_raw {
+    ($ptr:ident) => {
+        impl<'b, T: SpReadRaw<'b>> SpReadRaw<'b> for $ptr<T> {
+            fn inner_from_slice<'a>(
+                src: &mut &'b [u8],
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                ctx.check_alloc(size_of::<T>())?;
+
+                // Read directly into the allocation which cannot be shared yet
+                let mut p = $ptr::<T>::new_uninit();
+                let v = unsafe { $ptr::get_mut(&mut p).unwrap_unchecked() };
+                <T>::inner_from_slice(src, ctx, v)?;
+
+                dst.write(unsafe { p.assume_init() });
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+
+shared_read_raw!(Rc);
+#[cfg(target_has_atomic = "ptr")]
+shared_read_raw!(Arc);
+
+macro_rules! slice_ptr_read_raw {
+    ($ptr:ident) => {
+        impl<'b, T: SpReadRaw<'b>> SpReadRaw<'b> for $ptr<[T]> {
+            fn inner_from_slice<'a>(
+                src: &mut &'b [u8],
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                // Use the Vec<T> implementation
+                let mut tmp = MaybeUninit::uninit();
+                <Vec<T>>::inner_from_slice(src, ctx, &mut tmp)?;
+
+                dst.write($ptr::from(unsafe { tmp.assume_init() }));
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+
+        impl<'b> SpReadRaw<'b> for $ptr<str> {
+            fn inner_from_slice<'a>(
+                src: &mut &'b [u8],
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                // Use the String implementation
+                let mut tmp = MaybeUninit::uninit();
+                <String>::inner_from_slice(src, ctx, &mut tmp)?;
+
+                dst.write($ptr::from(unsafe { tmp.assume_init() }));
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+
+slice_ptr_read_raw!(Box);
+slice_ptr_read_raw!(Rc);
+#[cfg(target_has_atomic = "ptr")]
+slice_ptr_read_raw!(Arc);
+
+/// Borrows from the input through the `&T` implementation (e.g. `Cow<str>`, `Cow<[u8]>`)
+impl<'b: 'x, 'x, T: ToOwned + ?Sized> SpReadRaw<'b> for Cow<'x, T>
+where
+    &'x T: SpReadRaw<'b>,
+{
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut tmp = MaybeUninit::uninit();
+        <&'x T>::inner_from_slice(src, ctx, &mut tmp)?;
+
+        dst.write(Cow::Borrowed(unsafe { tmp.assume_init() }));
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
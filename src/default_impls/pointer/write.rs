@@ -0,0 +1,38 @@
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    rc::Rc,
+};
+
+use crate::*;
+
+macro_rules! ptr_write {
+    ($ptr:ident) => {
+        impl<T: SpWrite + ?Sized> SpWrite for $ptr<T> {
+            fn inner_to_writer<W: SpWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                (**self).inner_to_writer(ctx, dst)
+            }
+        }
+    };
+}
+
+ptr_write!(Box);
+ptr_write!(Rc);
+#[cfg(target_has_atomic = "ptr")]
+ptr_write!(Arc);
+
+impl<'x, T: SpWrite + ToOwned + ?Sized> SpWrite for Cow<'x, T> {
+    fn inner_to_writer<W: SpWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        (**self).inner_to_writer(ctx, dst)
+    }
+}
@@ -0,0 +1,83 @@
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::num::*;
+use core::sync::atomic::*;
+
+use crate::*;
+
+macro_rules! primitive_async_read {
+    ($($typ:ty),*) => {
+        $(
+        impl SpAsyncRead for $typ {
+            const IS_SAFE_REPR: bool = true;
+
+            unsafe fn validate_contents<'a>(
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                <Self as SpRead>::validate_contents(ctx, dst)
+            }
+
+            async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+            ) -> Result<Self, crate::SpError> {
+                let mut dst = MaybeUninit::uninit();
+                static_size_from_async_reader::<Self, R, { size_of::<$typ>() }>(src, ctx, &mut dst).await?;
+
+                // Re-use the endianness & content validation from SpRead
+                unsafe {
+                    <Self as SpRead>::validate_contents(ctx, &mut dst)?;
+                    Ok(dst.assume_init())
+                }
+            }
+        }
+        )*
+    };
+}
+
+primitive_async_read!(u8, u16, u32, u64, u128, usize);
+primitive_async_read!(i8, i16, i32, i64, i128, isize);
+primitive_async_read!(f32, f64, bool);
+#[cfg(target_has_atomic = "8")]
+primitive_async_read!(AtomicU8, AtomicI8, AtomicBool);
+#[cfg(target_has_atomic = "16")]
+primitive_async_read!(AtomicU16, AtomicI16);
+#[cfg(target_has_atomic = "32")]
+primitive_async_read!(AtomicU32, AtomicI32);
+#[cfg(target_has_atomic = "64")]
+primitive_async_read!(AtomicU64, AtomicI64);
+#[cfg(target_has_atomic = "ptr")]
+primitive_async_read!(AtomicUsize, AtomicIsize);
+primitive_async_read!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+
+impl SpAsyncRead for () {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        _src: &mut R,
+        _ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        Ok(())
+    }
+}
+
+impl<T: ?Sized> SpAsyncRead for PhantomData<T> {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        _src: &mut R,
+        _ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        Ok(PhantomData)
+    }
+}
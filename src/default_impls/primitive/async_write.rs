@@ -0,0 +1,74 @@
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::{num::*, sync::atomic::*};
+
+use crate::*;
+
+macro_rules! primitive_async_write {
+    ($($typ:ty),*) => {
+        $(
+        impl SpAsyncWrite for $typ {
+            async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                // Re-use the endianness handling from SpWrite
+                let mut buf = [0u8; size_of::<$typ>()];
+                let sz = self.inner_to_writer(ctx, &mut buf.as_mut_slice())?;
+
+                dst.async_write_bytes(&buf[..sz]).await?;
+                Ok(sz)
+            }
+        }
+        )*
+    };
+}
+
+primitive_async_write!(u8, u16, u32, u64, u128, usize);
+primitive_async_write!(i8, i16, i32, i64, i128, isize);
+primitive_async_write!(f32, f64, bool);
+#[cfg(target_has_atomic = "8")]
+primitive_async_write!(AtomicU8, AtomicI8, AtomicBool);
+#[cfg(target_has_atomic = "16")]
+primitive_async_write!(AtomicU16, AtomicI16);
+#[cfg(target_has_atomic = "32")]
+primitive_async_write!(AtomicU32, AtomicI32);
+#[cfg(target_has_atomic = "64")]
+primitive_async_write!(AtomicU64, AtomicI64);
+#[cfg(target_has_atomic = "ptr")]
+primitive_async_write!(AtomicUsize, AtomicIsize);
+primitive_async_write!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+
+impl SpAsyncWrite for () {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        _ctx: &mut SpCtx,
+        _dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        Ok(0)
+    }
+}
+
+impl<T: ?Sized> SpAsyncWrite for PhantomData<T> {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        _ctx: &mut SpCtx,
+        _dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        Ok(0)
+    }
+}
@@ -4,6 +4,10 @@
  * Atomic*, NonZero*
 */
 mod read;
+mod read_raw;
 mod write;
-pub use read::*;
-pub use write::*;
+
+#[cfg(feature = "async")]
+mod async_read;
+#[cfg(feature = "async")]
+mod async_write;
@@ -1,6 +1,7 @@
-use std::mem::size_of;
-use std::num::*;
-use std::sync::atomic::*;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::num::*;
+use core::sync::atomic::*;
 
 use crate::*;
 
@@ -29,7 +30,7 @@ macro_rules! primitive_read {
                 Ok(v)
             }
 
-            fn inner_from_reader<'a, R: Read + ?Sized>(
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
                 // Data source
                 src: &mut R,
                 // Parsing context
@@ -39,7 +40,7 @@ macro_rules! primitive_read {
             ) -> Result<&'a mut Self, crate::SpError> {
                 static_size_from_reader::<Self, R, { size_of::<$typ>() }>(src, ctx, dst)?;
 
-                let v = unsafe { Self::validate_contents(ctx, dst)? };
+                let v = unsafe { <Self as SpRead>::validate_contents(ctx, dst)? };
 
                 Ok(v)
             }
@@ -62,15 +63,25 @@ primitive_read!(isize);
 primitive_read!(f32);
 primitive_read!(f64);
 
+#[cfg(target_has_atomic = "8")]
 primitive_read!(AtomicU8);
+#[cfg(target_has_atomic = "16")]
 primitive_read!(AtomicU16);
+#[cfg(target_has_atomic = "32")]
 primitive_read!(AtomicU32);
+#[cfg(target_has_atomic = "64")]
 primitive_read!(AtomicU64);
+#[cfg(target_has_atomic = "ptr")]
 primitive_read!(AtomicUsize);
+#[cfg(target_has_atomic = "8")]
 primitive_read!(AtomicI8);
+#[cfg(target_has_atomic = "16")]
 primitive_read!(AtomicI16);
+#[cfg(target_has_atomic = "32")]
 primitive_read!(AtomicI32);
+#[cfg(target_has_atomic = "64")]
 primitive_read!(AtomicI64);
+#[cfg(target_has_atomic = "ptr")]
 primitive_read!(AtomicIsize);
 
 macro_rules! nonzero_read {
@@ -104,7 +115,7 @@ macro_rules! nonzero_read {
                 Ok(dst.assume_init_mut())
             }
 
-            fn inner_from_reader<'a, R: Read + ?Sized>(
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
                 // Data source
                 src: &mut R,
                 // Parsing context
@@ -114,7 +125,7 @@ macro_rules! nonzero_read {
             ) -> Result<&'a mut Self, crate::SpError> {
                 static_size_from_reader::<Self, R, { size_of::<$typ>() }>(src, ctx, dst)?;
 
-                let v = unsafe { Self::validate_contents(ctx, dst)? };
+                let v = unsafe { <Self as SpRead>::validate_contents(ctx, dst)? };
 
                 #[cfg(feature = "verbose")]
                 ::log::debug!("0x{v:X?}");
@@ -157,7 +168,7 @@ impl SpRead for bool {
         Ok(dst.assume_init_mut())
     }
 
-    fn inner_from_reader<'a, R: Read + ?Sized>(
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
         // Data source
         src: &mut R,
         // Parsing context
@@ -171,7 +182,7 @@ impl SpRead for bool {
         let u8_dst = unsafe { &mut *(dst as *mut _ as *mut MaybeUninit<u8>) };
         <u8>::inner_from_reader(src, ctx, u8_dst)?;
 
-        let v = unsafe { Self::validate_contents(ctx, dst)? };
+        let v = unsafe { <Self as SpRead>::validate_contents(ctx, dst)? };
 
         #[cfg(feature = "verbose")]
         ::log::debug!("{v:?}");
@@ -180,6 +191,7 @@ impl SpRead for bool {
     }
 }
 
+#[cfg(target_has_atomic = "8")]
 impl SpRead for AtomicBool {
     const STATIC_CHECKS: () = {
         // Make sure we can safely cast between the two types
@@ -199,7 +211,7 @@ impl SpRead for AtomicBool {
         Ok(dst.assume_init_mut())
     }
 
-    fn inner_from_reader<'a, R: Read + ?Sized>(
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
         // Data source
         src: &mut R,
         // Parsing context
@@ -212,11 +224,30 @@ impl SpRead for AtomicBool {
         let u8_dst = unsafe { &mut *(dst as *mut _ as *mut MaybeUninit<u8>) };
         <u8>::inner_from_reader(src, ctx, u8_dst)?;
 
-        let v = unsafe { Self::validate_contents(ctx, dst)? };
+        let v = unsafe { <Self as SpRead>::validate_contents(ctx, dst)? };
 
         #[cfg(feature = "verbose")]
         ::log::debug!("{v:?}");
 
         Ok(v)
     }
-}
\ No newline at end of file
+}
+
+/// Zero sized types do not consume any bytes
+macro_rules! zero_sized_read {
+    ($typ:ty $(, $generic:ident)?) => {
+        impl$(<$generic: ?Sized>)? SpRead for $typ {
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
+                _src: &mut R,
+                _ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                dst.write(Default::default());
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+
+zero_sized_read!(());
+zero_sized_read!(PhantomData<T>, T);
@@ -0,0 +1,79 @@
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::num::*;
+use core::sync::atomic::*;
+
+use crate::*;
+
+macro_rules! primitive_read_raw {
+    ($($typ:ty),*) => {
+        $(
+        impl<'b> SpReadRaw<'b> for $typ {
+            const IS_SAFE_REPR: bool = true;
+
+            fn inner_from_slice<'a>(
+                src: &mut &'b [u8],
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                static_size_from_slice::<Self, { size_of::<$typ>() }>(src, ctx, dst)?;
+
+                // Re-use the endianness & content validation from SpRead
+                let v = unsafe { <Self as SpRead>::validate_contents(ctx, dst)? };
+
+                Ok(v)
+            }
+        }
+        )*
+    };
+}
+
+primitive_read_raw!(u8, u16, u32, u64, u128, usize);
+primitive_read_raw!(i8, i16, i32, i64, i128, isize);
+primitive_read_raw!(f32, f64, bool);
+#[cfg(target_has_atomic = "8")]
+primitive_read_raw!(AtomicU8, AtomicI8, AtomicBool);
+#[cfg(target_has_atomic = "16")]
+primitive_read_raw!(AtomicU16, AtomicI16);
+#[cfg(target_has_atomic = "32")]
+primitive_read_raw!(AtomicU32, AtomicI32);
+#[cfg(target_has_atomic = "64")]
+primitive_read_raw!(AtomicU64, AtomicI64);
+#[cfg(target_has_atomic = "ptr")]
+primitive_read_raw!(AtomicUsize, AtomicIsize);
+primitive_read_raw!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+
+impl<'b> SpReadRaw<'b> for () {
+    fn inner_from_slice<'a>(
+        _src: &mut &'b [u8],
+        _ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        dst.write(());
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
+
+impl<'b, T: ?Sized> SpReadRaw<'b> for PhantomData<T> {
+    fn inner_from_slice<'a>(
+        _src: &mut &'b [u8],
+        _ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        dst.write(PhantomData);
+        Ok(unsafe { dst.assume_init_mut() })
+    }
+}
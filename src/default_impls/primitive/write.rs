@@ -1,7 +1,8 @@
+use core::marker::PhantomData;
 use core::mem::size_of;
-use std::{num::*, sync::atomic::*};
+use core::{num::*, sync::atomic::*};
 
-use crate::{SpError, SpWrite};
+use crate::{SpWrite, SpWriter};
 
 macro_rules! primitive_write {
     ($typ:ty) => {
@@ -9,7 +10,7 @@ macro_rules! primitive_write {
     };
     ($typ:ty, $as_typ: ty) => {
         impl SpWrite for $typ {
-            fn inner_to_writer<W: std::io::Write + ?Sized>(
+            fn inner_to_writer<W: SpWriter + ?Sized>(
                 &self,
                 ctx: &mut crate::SpCtx,
                 dst: &mut W,
@@ -23,9 +24,7 @@ macro_rules! primitive_write {
                     buf.reverse();
                 }
 
-                if dst.write_all(buf).is_err() {
-                    return Err(SpError::NotEnoughSpace);
-                }
+                dst.write_bytes(buf)?;
 
                 ctx.cursor += size_of::<$as_typ>();
                 Ok(size_of::<$as_typ>())
@@ -50,7 +49,7 @@ primitive_write!(f32, u32);
 primitive_write!(f64, u64);
 
 impl SpWrite for bool {
-    fn inner_to_writer<W: std::io::Write + ?Sized>(
+    fn inner_to_writer<W: SpWriter + ?Sized>(
         &self,
         ctx: &mut crate::SpCtx,
         dst: &mut W,
@@ -60,19 +59,30 @@ impl SpWrite for bool {
     }
 }
 
+#[cfg(target_has_atomic = "8")]
 primitive_write!(AtomicU8, u8);
+#[cfg(target_has_atomic = "16")]
 primitive_write!(AtomicU16, u16);
+#[cfg(target_has_atomic = "32")]
 primitive_write!(AtomicU32, u32);
+#[cfg(target_has_atomic = "64")]
 primitive_write!(AtomicU64, u64);
+#[cfg(target_has_atomic = "ptr")]
 primitive_write!(AtomicUsize, usize);
+#[cfg(target_has_atomic = "8")]
 primitive_write!(AtomicI8, i8);
+#[cfg(target_has_atomic = "16")]
 primitive_write!(AtomicI16, i16);
+#[cfg(target_has_atomic = "32")]
 primitive_write!(AtomicI32, i32);
+#[cfg(target_has_atomic = "64")]
 primitive_write!(AtomicI64, i64);
+#[cfg(target_has_atomic = "ptr")]
 primitive_write!(AtomicIsize, isize);
 
+#[cfg(target_has_atomic = "8")]
 impl SpWrite for AtomicBool {
-    fn inner_to_writer<W: std::io::Write + ?Sized>(
+    fn inner_to_writer<W: SpWriter + ?Sized>(
         &self,
         ctx: &mut crate::SpCtx,
         dst: &mut W,
@@ -94,3 +104,21 @@ primitive_write!(NonZeroI32, i32);
 primitive_write!(NonZeroI64, i64);
 primitive_write!(NonZeroI128, i128);
 primitive_write!(NonZeroIsize, isize);
+
+/// Zero sized types do not produce any bytes
+macro_rules! zero_sized_write {
+    ($typ:ty $(, $generic:ident)?) => {
+        impl$(<$generic: ?Sized>)? SpWrite for $typ {
+            fn inner_to_writer<W: SpWriter + ?Sized>(
+                &self,
+                _ctx: &mut crate::SpCtx,
+                _dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                Ok(0)
+            }
+        }
+    };
+}
+
+zero_sized_write!(());
+zero_sized_write!(PhantomData<T>, T);
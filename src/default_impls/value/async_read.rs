@@ -0,0 +1,52 @@
+use core::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use super::{SpWire, TAG_V4, TAG_V6};
+use crate::*;
+
+macro_rules! wire_async_read {
+    ($typ:ty) => {
+        impl SpAsyncRead for $typ {
+            async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+            ) -> Result<Self, crate::SpError> {
+                let v = <<Self as SpWire>::Wire>::inner_from_async_reader(src, ctx).await?;
+                <Self as SpWire>::from_wire(v)
+            }
+        }
+    };
+}
+
+wire_async_read!(char);
+wire_async_read!(Duration);
+#[cfg(feature = "std")]
+wire_async_read!(SystemTime);
+wire_async_read!(Ipv4Addr);
+wire_async_read!(Ipv6Addr);
+wire_async_read!(SocketAddrV4);
+wire_async_read!(SocketAddrV6);
+
+macro_rules! tagged_async_read {
+    ($typ:ident, $v4:ty, $v6:ty) => {
+        impl SpAsyncRead for $typ {
+            async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+            ) -> Result<Self, crate::SpError> {
+                match <u8>::inner_from_async_reader(src, ctx).await? {
+                    TAG_V4 => Ok($typ::V4(<$v4>::inner_from_async_reader(src, ctx).await?)),
+                    TAG_V6 => Ok($typ::V6(<$v6>::inner_from_async_reader(src, ctx).await?)),
+                    _ => Err(SpError::InvalidBytes),
+                }
+            }
+        }
+    };
+}
+
+tagged_async_read!(IpAddr, Ipv4Addr, Ipv6Addr);
+tagged_async_read!(SocketAddr, SocketAddrV4, SocketAddrV6);
@@ -0,0 +1,60 @@
+use core::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use super::{SpWire, TAG_V4, TAG_V6};
+use crate::*;
+
+macro_rules! wire_async_write {
+    ($typ:ty) => {
+        impl SpAsyncWrite for $typ {
+            async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                <Self as SpWire>::to_wire(self)?
+                    .inner_to_async_writer(ctx, dst)
+                    .await
+            }
+        }
+    };
+}
+
+wire_async_write!(char);
+wire_async_write!(Duration);
+#[cfg(feature = "std")]
+wire_async_write!(SystemTime);
+wire_async_write!(Ipv4Addr);
+wire_async_write!(Ipv6Addr);
+wire_async_write!(SocketAddrV4);
+wire_async_write!(SocketAddrV6);
+
+macro_rules! tagged_async_write {
+    ($typ:ident) => {
+        impl SpAsyncWrite for $typ {
+            async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                Ok(match self {
+                    $typ::V4(v) => {
+                        TAG_V4.inner_to_async_writer(ctx, dst).await?
+                            + v.inner_to_async_writer(ctx, dst).await?
+                    }
+                    $typ::V6(v) => {
+                        TAG_V6.inner_to_async_writer(ctx, dst).await?
+                            + v.inner_to_async_writer(ctx, dst).await?
+                    }
+                })
+            }
+        }
+    };
+}
+
+tagged_async_write!(IpAddr);
+tagged_async_write!(SocketAddr);
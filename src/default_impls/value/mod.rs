@@ -0,0 +1,165 @@
+/** Implements simple_parse traits on std value types such as :
+ * char, Duration, SystemTime
+ * Ipv4Addr, Ipv6Addr, IpAddr, SocketAddrV4, SocketAddrV6, SocketAddr
+ *
+ * Wire layouts (integers follow the endianness of the SpCtx) :
+ * | Type           | Layout                                                       |
+ * |----------------|--------------------------------------------------------------|
+ * | char           | u32 unicode scalar value                                     |
+ * | Duration       | u64 seconds, u32 nanoseconds (< 1_000_000_000)               |
+ * | SystemTime     | i64 seconds since UNIX_EPOCH, u32 nanoseconds added on top   |
+ * | Ipv4Addr       | [u8; 4] octets in network order                              |
+ * | Ipv6Addr       | [u8; 16] octets in network order                             |
+ * | IpAddr         | u8 tag (4 or 6), Ipv4Addr or Ipv6Addr                        |
+ * | SocketAddrV4   | Ipv4Addr, u16 port                                           |
+ * | SocketAddrV6   | Ipv6Addr, u16 port, u32 flowinfo, u32 scope_id               |
+ * | SocketAddr     | u8 tag (4 or 6), SocketAddrV4 or SocketAddrV6                |
+*/
+mod read;
+mod read_raw;
+mod write;
+
+#[cfg(feature = "async")]
+mod async_read;
+#[cfg(feature = "async")]
+mod async_write;
+
+use core::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+#[cfg(feature = "std")]
+use std::{
+    convert::TryFrom,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::*;
+
+/// Tag written before IpAddr::V4 and SocketAddr::V4
+const TAG_V4: u8 = 4;
+/// Tag written before IpAddr::V6 and SocketAddr::V6
+const TAG_V6: u8 = 6;
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Types that are sent as another type which already implements every trait
+trait SpWire: Sized {
+    type Wire;
+    /// Validates and converts the parsed wire value
+    fn from_wire(v: Self::Wire) -> Result<Self, SpError>;
+    /// Converts self into the value to write
+    fn to_wire(&self) -> Result<Self::Wire, SpError>;
+}
+
+impl SpWire for char {
+    type Wire = u32;
+    fn from_wire(v: u32) -> Result<Self, SpError> {
+        char::from_u32(v).ok_or(SpError::InvalidBytes)
+    }
+    fn to_wire(&self) -> Result<u32, SpError> {
+        Ok(*self as u32)
+    }
+}
+
+impl SpWire for Duration {
+    type Wire = (u64, u32);
+    fn from_wire((secs, nanos): (u64, u32)) -> Result<Self, SpError> {
+        // Duration::new() would carry the extra nanos into secs
+        if nanos >= NANOS_PER_SEC {
+            return Err(SpError::InvalidBytes);
+        }
+        Ok(Duration::new(secs, nanos))
+    }
+    fn to_wire(&self) -> Result<(u64, u32), SpError> {
+        Ok((self.as_secs(), self.subsec_nanos()))
+    }
+}
+
+#[cfg(feature = "std")]
+impl SpWire for SystemTime {
+    type Wire = (i64, u32);
+    fn from_wire((secs, nanos): (i64, u32)) -> Result<Self, SpError> {
+        if nanos >= NANOS_PER_SEC {
+            return Err(SpError::InvalidBytes);
+        }
+        let t = if secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos))
+        } else {
+            UNIX_EPOCH
+                .checked_sub(Duration::from_secs(secs.unsigned_abs()))
+                .and_then(|t| t.checked_add(Duration::from_nanos(nanos as u64)))
+        };
+        // The platform cannot represent this time
+        t.ok_or(SpError::InvalidBytes)
+    }
+    fn to_wire(&self) -> Result<(i64, u32), SpError> {
+        let (secs, nanos) = match self.duration_since(UNIX_EPOCH) {
+            Ok(d) => (i64::try_from(d.as_secs()), d.subsec_nanos()),
+            Err(e) => {
+                // Round the seconds down so that nanos stays positive
+                let d = e.duration();
+                match d.subsec_nanos() {
+                    0 => (i64::try_from(d.as_secs()).map(|s| -s), 0),
+                    n => (
+                        i64::try_from(d.as_secs()).map(|s| -s - 1),
+                        NANOS_PER_SEC - n,
+                    ),
+                }
+            }
+        };
+        Ok((secs.map_err(|_| SpError::InvalidBytes)?, nanos))
+    }
+}
+
+impl SpWire for Ipv4Addr {
+    type Wire = [u8; 4];
+    fn from_wire(v: [u8; 4]) -> Result<Self, SpError> {
+        Ok(Ipv4Addr::from(v))
+    }
+    fn to_wire(&self) -> Result<[u8; 4], SpError> {
+        Ok(self.octets())
+    }
+}
+
+impl SpWire for Ipv6Addr {
+    type Wire = [u8; 16];
+    fn from_wire(v: [u8; 16]) -> Result<Self, SpError> {
+        Ok(Ipv6Addr::from(v))
+    }
+    fn to_wire(&self) -> Result<[u8; 16], SpError> {
+        Ok(self.octets())
+    }
+}
+
+impl SpWire for SocketAddrV4 {
+    type Wire = ([u8; 4], u16);
+    fn from_wire((ip, port): ([u8; 4], u16)) -> Result<Self, SpError> {
+        Ok(SocketAddrV4::new(Ipv4Addr::from(ip), port))
+    }
+    fn to_wire(&self) -> Result<([u8; 4], u16), SpError> {
+        Ok((self.ip().octets(), self.port()))
+    }
+}
+
+impl SpWire for SocketAddrV6 {
+    type Wire = ([u8; 16], u16, u32, u32);
+    fn from_wire(
+        (ip, port, flowinfo, scope_id): ([u8; 16], u16, u32, u32),
+    ) -> Result<Self, SpError> {
+        Ok(SocketAddrV6::new(
+            Ipv6Addr::from(ip),
+            port,
+            flowinfo,
+            scope_id,
+        ))
+    }
+    fn to_wire(&self) -> Result<([u8; 16], u16, u32, u32), SpError> {
+        Ok((
+            self.ip().octets(),
+            self.port(),
+            self.flowinfo(),
+            self.scope_id(),
+        ))
+    }
+}
@@ -0,0 +1,69 @@
+use core::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use super::{SpWire, TAG_V4, TAG_V6};
+use crate::*;
+
+macro_rules! wire_read {
+    ($typ:ty) => {
+        impl SpRead for $typ {
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                let mut tmp = MaybeUninit::uninit();
+                let v = *<<Self as SpWire>::Wire>::inner_from_reader(src, ctx, &mut tmp)?;
+
+                dst.write(<Self as SpWire>::from_wire(v)?);
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+
+wire_read!(char);
+wire_read!(Duration);
+#[cfg(feature = "std")]
+wire_read!(SystemTime);
+wire_read!(Ipv4Addr);
+wire_read!(Ipv6Addr);
+wire_read!(SocketAddrV4);
+wire_read!(SocketAddrV6);
+
+macro_rules! tagged_read {
+    ($typ:ident, $v4:ty, $v6:ty) => {
+        impl SpRead for $typ {
+            fn inner_from_reader<'a, R: SpReader + ?Sized>(
+                src: &mut R,
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                let mut tag = MaybeUninit::uninit();
+                let v = match *<u8>::inner_from_reader(src, ctx, &mut tag)? {
+                    TAG_V4 => $typ::V4(*<$v4>::inner_from_reader(
+                        src,
+                        ctx,
+                        &mut MaybeUninit::uninit(),
+                    )?),
+                    TAG_V6 => $typ::V6(*<$v6>::inner_from_reader(
+                        src,
+                        ctx,
+                        &mut MaybeUninit::uninit(),
+                    )?),
+                    _ => return Err(SpError::InvalidBytes),
+                };
+
+                dst.write(v);
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+
+tagged_read!(IpAddr, Ipv4Addr, Ipv6Addr);
+tagged_read!(SocketAddr, SocketAddrV4, SocketAddrV6);
@@ -0,0 +1,69 @@
+use core::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use super::{SpWire, TAG_V4, TAG_V6};
+use crate::*;
+
+macro_rules! wire_read_raw {
+    ($typ:ty) => {
+        impl<'b> SpReadRaw<'b> for $typ {
+            fn inner_from_slice<'a>(
+                src: &mut &'b [u8],
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                let mut tmp = MaybeUninit::uninit();
+                let v = *<<Self as SpWire>::Wire>::inner_from_slice(src, ctx, &mut tmp)?;
+
+                dst.write(<Self as SpWire>::from_wire(v)?);
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+
+wire_read_raw!(char);
+wire_read_raw!(Duration);
+#[cfg(feature = "std")]
+wire_read_raw!(SystemTime);
+wire_read_raw!(Ipv4Addr);
+wire_read_raw!(Ipv6Addr);
+wire_read_raw!(SocketAddrV4);
+wire_read_raw!(SocketAddrV6);
+
+macro_rules! tagged_read_raw {
+    ($typ:ident, $v4:ty, $v6:ty) => {
+        impl<'b> SpReadRaw<'b> for $typ {
+            fn inner_from_slice<'a>(
+                src: &mut &'b [u8],
+                ctx: &mut SpCtx,
+                dst: &'a mut MaybeUninit<Self>,
+            ) -> Result<&'a mut Self, crate::SpError> {
+                let mut tag = MaybeUninit::uninit();
+                let v = match *<u8>::inner_from_slice(src, ctx, &mut tag)? {
+                    TAG_V4 => $typ::V4(*<$v4>::inner_from_slice(
+                        src,
+                        ctx,
+                        &mut MaybeUninit::uninit(),
+                    )?),
+                    TAG_V6 => $typ::V6(*<$v6>::inner_from_slice(
+                        src,
+                        ctx,
+                        &mut MaybeUninit::uninit(),
+                    )?),
+                    _ => return Err(SpError::InvalidBytes),
+                };
+
+                dst.write(v);
+                Ok(unsafe { dst.assume_init_mut() })
+            }
+        }
+    };
+}
+
+tagged_read_raw!(IpAddr, Ipv4Addr, Ipv6Addr);
+tagged_read_raw!(SocketAddr, SocketAddrV4, SocketAddrV6);
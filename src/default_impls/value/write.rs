@@ -0,0 +1,56 @@
+use core::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
+};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use super::{SpWire, TAG_V4, TAG_V6};
+use crate::*;
+
+macro_rules! wire_write {
+    ($typ:ty) => {
+        impl SpWrite for $typ {
+            fn inner_to_writer<W: SpWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                <Self as SpWire>::to_wire(self)?.inner_to_writer(ctx, dst)
+            }
+        }
+    };
+}
+
+wire_write!(char);
+wire_write!(Duration);
+#[cfg(feature = "std")]
+wire_write!(SystemTime);
+wire_write!(Ipv4Addr);
+wire_write!(Ipv6Addr);
+wire_write!(SocketAddrV4);
+wire_write!(SocketAddrV6);
+
+macro_rules! tagged_write {
+    ($typ:ident) => {
+        impl SpWrite for $typ {
+            fn inner_to_writer<W: SpWriter + ?Sized>(
+                &self,
+                ctx: &mut SpCtx,
+                dst: &mut W,
+            ) -> Result<usize, crate::SpError> {
+                Ok(match self {
+                    $typ::V4(v) => {
+                        TAG_V4.inner_to_writer(ctx, dst)? + v.inner_to_writer(ctx, dst)?
+                    }
+                    $typ::V6(v) => {
+                        TAG_V6.inner_to_writer(ctx, dst)? + v.inner_to_writer(ctx, dst)?
+                    }
+                })
+            }
+        }
+    };
+}
+
+tagged_write!(IpAddr);
+tagged_write!(SocketAddr);
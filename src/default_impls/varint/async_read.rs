@@ -0,0 +1,27 @@
+use crate::varint::VarDecoder;
+use crate::*;
+
+impl SpAsyncRead for VarU64 {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        let mut decoder = VarDecoder::default();
+        loop {
+            let b = <u8>::inner_from_async_reader(src, ctx).await?;
+            if let Some(v) = decoder.push(b)? {
+                return Ok(v);
+            }
+        }
+    }
+}
+
+impl SpAsyncRead for VarI64 {
+    async fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> Result<Self, crate::SpError> {
+        let v = <VarU64>::inner_from_async_reader(src, ctx).await?;
+        Ok(VarI64::unzigzag(v))
+    }
+}
@@ -0,0 +1,27 @@
+use crate::varint::MAX_VARINT_LEN;
+use crate::*;
+
+impl SpAsyncWrite for VarU64 {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        let mut buf = [0u8; MAX_VARINT_LEN];
+        let bytes = self.encode(&mut buf);
+        dst.async_write_bytes(bytes).await?;
+
+        ctx.cursor += bytes.len();
+        Ok(bytes.len())
+    }
+}
+
+impl SpAsyncWrite for VarI64 {
+    async fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.zigzag().inner_to_async_writer(ctx, dst).await
+    }
+}
@@ -0,0 +1,11 @@
+/** Implements simple_parse traits on LEB128 encoded integers :
+ * VarU64, VarI64
+*/
+mod read;
+mod read_raw;
+mod write;
+
+#[cfg(feature = "async")]
+mod async_read;
+#[cfg(feature = "async")]
+mod async_write;
@@ -0,0 +1,31 @@
+use crate::varint::VarDecoder;
+use crate::*;
+
+impl SpRead for VarU64 {
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut decoder = VarDecoder::default();
+        let mut tmp = MaybeUninit::uninit();
+        loop {
+            let b = *<u8>::inner_from_reader(src, ctx, &mut tmp)?;
+            if let Some(v) = decoder.push(b)? {
+                return Ok(dst.write(v));
+            }
+        }
+    }
+}
+
+impl SpRead for VarI64 {
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut tmp = MaybeUninit::uninit();
+        let v = *<VarU64>::inner_from_reader(src, ctx, &mut tmp)?;
+        Ok(dst.write(VarI64::unzigzag(v)))
+    }
+}
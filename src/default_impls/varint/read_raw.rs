@@ -0,0 +1,31 @@
+use crate::varint::VarDecoder;
+use crate::*;
+
+impl<'b> SpReadRaw<'b> for VarU64 {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut decoder = VarDecoder::default();
+        let mut tmp = MaybeUninit::uninit();
+        loop {
+            let b = *<u8>::inner_from_slice(src, ctx, &mut tmp)?;
+            if let Some(v) = decoder.push(b)? {
+                return Ok(dst.write(v));
+            }
+        }
+    }
+}
+
+impl<'b> SpReadRaw<'b> for VarI64 {
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut tmp = MaybeUninit::uninit();
+        let v = *<VarU64>::inner_from_slice(src, ctx, &mut tmp)?;
+        Ok(dst.write(VarI64::unzigzag(v)))
+    }
+}
@@ -0,0 +1,27 @@
+use crate::varint::MAX_VARINT_LEN;
+use crate::*;
+
+impl SpWrite for VarU64 {
+    fn inner_to_writer<W: SpWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        let mut buf = [0u8; MAX_VARINT_LEN];
+        let bytes = self.encode(&mut buf);
+        dst.write_bytes(bytes)?;
+
+        ctx.cursor += bytes.len();
+        Ok(bytes.len())
+    }
+}
+
+impl SpWrite for VarI64 {
+    fn inner_to_writer<W: SpWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.zigzag().inner_to_writer(ctx, dst)
+    }
+}
@@ -1,44 +1,272 @@
-use std::error;
-use std::fmt;
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::fmt;
 
 /// Possible errors when reading/writing
 #[derive(Debug)]
 pub enum SpError {
     /// Could not read bytes from reader
+    #[cfg(feature = "std")]
     ReadFailed(std::io::Error),
+    /// Could not write bytes into the writer
+    #[cfg(feature = "std")]
+    WriteFailed(std::io::Error),
     /// The data we attempted to decode did not contain a valid enum variant
     UnknownEnumVariant,
-    /// There is not enough space to write T into the writer or to read T from the reader
+    /// There is not enough space to write T into a bounded destination or to read T from the input
     NotEnoughSpace,
-    /// An annotated `len` field's type is too small to fit the number of elements
+    /// An annotated `len` field's type or the `len_type` is too small to fit the number of elements.
+    /// Also returned when a `calc` expression overflows or does not fit in its field
     CountFieldOverflow,
+    /// A value does not fit in the number of bits of its `#[sp(bits)]` field
+    BitFieldOverflow,
+    /// A value is longer than the number of bytes of its `#[sp(fixed_len)]` field
+    FixedLenOverflow {
+        /// Size of the field
+        max: usize,
+        /// Size of the value
+        len: usize,
+    },
+    /// The value of a `len` expression does not match the field it describes when writing
+    LenMismatch {
+        /// Value of the `len` expression
+        expected: usize,
+        /// Number of elements (or bytes for `byte_len`) of the field
+        len: usize,
+    },
+    /// The `cond` of an `Option` field does not match whether it is `Some` when writing
+    CondMismatch,
+    /// The value of an `#[sp(until)]` field contains its terminator
+    AmbiguousTerminator,
+    /// The value of a `#[sp(fixed_len)]` field ends with its padding byte, which would be stripped when reading
+    AmbiguousPadding,
+    /// Padding that was read does not only contain the padding byte of a `#[sp(strict_pad)]` field
+    BadPadding {
+        /// The padding byte
+        expected: u8,
+        /// First byte that differs from it
+        found: u8,
+    },
+    /// The bytes of a `magic` or `const` did not match when reading
+    BadMagic {
+        /// Bytes of the `magic` or `const`
+        expected: Vec<u8>,
+        /// Bytes that were read instead
+        found: Vec<u8>,
+    },
+    /// The value of a `checksum` field did not match the bytes it covers when reading
+    BadChecksum {
+        /// Checksum computed over the bytes that were read
+        expected: u64,
+        /// Value of the field
+        found: u64,
+    },
     /// The data contained enough bytes but the contents were invalid
     InvalidBytes,
     /// A Rust reference cannot be created as the data is misaligned
     BadAlignment,
+    /// One of the [SpLimits](crate::SpLimits) was exceeded
+    LimitExceeded {
+        /// The limit that was exceeded
+        limit: SpLimit,
+        /// Value of the limit
+        max: usize,
+        /// Value that would have been reached
+        requested: usize,
+    },
+    /// An error that happened while processing a field of a derived type
+    Context(Box<SpErrorContext>),
+    /// An error reported by a custom reader, writer or validator
+    Custom(Box<dyn core::error::Error + Send + Sync>),
+}
+
+/// Identifies one of the [SpLimits](crate::SpLimits)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpLimit {
+    /// `max_bytes`
+    Bytes,
+    /// `max_count`
+    Count,
+    /// `max_alloc`
+    Alloc,
+    /// `max_depth`
+    Depth,
+}
+
+/// Describes where an error happened within a derived type
+#[derive(Debug)]
+pub struct SpErrorContext {
+    /// Value of `SpCtx.cursor` when the error happened
+    pub offset: usize,
+    /// Name of the outermost type
+    pub root: &'static str,
+    /// Field names from the innermost to the outermost
+    fields: Vec<&'static str>,
+    /// Name of the innermost field's type
+    pub type_name: &'static str,
+    /// The underlying error
+    pub error: SpError,
+}
+
+impl SpErrorContext {
+    /// Returns the path to the failing field (e.g. `BmpHeader.dib.compression`)
+    pub fn path(&self) -> String {
+        let mut path = String::from(self.root);
+        for field in self.fields.iter().rev() {
+            path.push('.');
+            path.push_str(field);
+        }
+        path
+    }
+}
+
+impl SpError {
+    /// Adds `parent.field` to the path of this error.
+    ///
+    /// The offset and type name are only recorded the first time an error is wrapped
+    pub fn in_field(
+        self,
+        parent: &'static str,
+        field: &'static str,
+        type_name: &'static str,
+        offset: usize,
+    ) -> Self {
+        match self {
+            SpError::Context(mut c) => {
+                c.fields.push(field);
+                c.root = parent;
+                SpError::Context(c)
+            }
+            error => SpError::Context(Box::new(SpErrorContext {
+                offset,
+                root: parent,
+                fields: vec![field],
+                type_name,
+                error,
+            })),
+        }
+    }
+
+    /// Wraps any error from a custom reader, writer or validator
+    pub fn custom<E: Into<Box<dyn core::error::Error + Send + Sync>>>(error: E) -> Self {
+        SpError::Custom(error.into())
+    }
+
+    /// Creates a custom error from a message
+    pub fn msg<M: Into<String>>(msg: M) -> Self {
+        SpError::Custom(msg.into().into())
+    }
+
+    /// Converts an I/O error from a destination into a `SpError`
+    ///
+    /// Bounded destinations like `&mut [u8]` report being full with `WriteZero`, which becomes `NotEnoughSpace`.
+    /// Custom writers should use it instead of `?`, which reports I/O errors as `ReadFailed`
+    #[cfg(feature = "std")]
+    pub fn from_write_error(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::WriteZero {
+            SpError::NotEnoughSpace
+        } else {
+            SpError::WriteFailed(e)
+        }
+    }
+
+    /// Returns the underlying error without its context
+    pub fn kind(&self) -> &SpError {
+        match self {
+            SpError::Context(c) => &c.error,
+            e => e,
+        }
+    }
 }
 impl fmt::Display for SpError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            #[cfg(feature = "std")]
             SpError::ReadFailed(e) => write!(f, "Failed to read more bytes : {e}"),
+            #[cfg(feature = "std")]
+            SpError::WriteFailed(e) => write!(f, "Failed to write bytes : {e}"),
             SpError::UnknownEnumVariant => write!(f, "Encountered invalid enum variant ID"),
             SpError::NotEnoughSpace => {
                 write!(f, "Not enough bytes in the buffer to parse wanted type")
             }
             SpError::CountFieldOverflow => write!(
                 f,
-                "The `len` field's type is too small for the number of items !"
+                "The `len` field's type or `len_type` is too small for the number of items !"
+            ),
+            SpError::BitFieldOverflow => write!(f, "Value is too wide for its bit field"),
+            SpError::FixedLenOverflow { max, len } => {
+                write!(
+                    f,
+                    "Value of {len} bytes does not fit in a field of {max} bytes"
+                )
+            }
+            SpError::LenMismatch { expected, len } => {
+                write!(
+                    f,
+                    "The `len` expression is {expected} but the field has a length of {len}"
+                )
+            }
+            SpError::CondMismatch => write!(
+                f,
+                "The field's condition does not match whether it is present"
             ),
+            SpError::AmbiguousTerminator => write!(f, "Value contains its terminator"),
+            SpError::AmbiguousPadding => write!(f, "Value ends with its padding byte"),
+            SpError::BadPadding { expected, found } => {
+                write!(
+                    f,
+                    "Expected padding bytes of {expected:#04X} but found {found:#04X}"
+                )
+            }
+            SpError::BadMagic { expected, found } => {
+                write!(
+                    f,
+                    "Expected the bytes {expected:02X?} but found {found:02X?}"
+                )
+            }
+            SpError::BadChecksum { expected, found } => {
+                write!(
+                    f,
+                    "Expected the checksum {expected:#X} but found {found:#X}"
+                )
+            }
             SpError::InvalidBytes => write!(f, "Failed to parse the bytes into the wanted type"),
             SpError::BadAlignment => write!(f, "Input bytes are misaligned"),
+            SpError::LimitExceeded {
+                limit,
+                max,
+                requested,
+            } => write!(f, "Limit {limit:?} exceeded : {requested} > {max}"),
+            SpError::Context(c) => write!(
+                f,
+                "{} ({} : {} at offset {})",
+                c.error,
+                c.path(),
+                c.type_name,
+                c.offset
+            ),
+            SpError::Custom(e) => write!(f, "{e}"),
         }
     }
 }
-impl error::Error for SpError {
-    fn cause(&self) -> Option<&dyn error::Error> {
+#[cfg(feature = "std")]
+impl std::error::Error for SpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            SpError::ReadFailed(e) => Some(e),
+            SpError::ReadFailed(e) | SpError::WriteFailed(e) => Some(e),
+            SpError::Context(c) => Some(&c.error),
+            SpError::Custom(e) => Some(e.as_ref()),
             _ => None,
         }
     }
 }
+/// Reports I/O errors as [SpError::ReadFailed]
+///
+/// `?` cannot tell whether an I/O error comes from a reader or a writer. Custom writers must convert
+/// their errors with [SpError::from_write_error] instead, e.g.
+/// `dst.write_all(buf).map_err(SpError::from_write_error)?`
+#[cfg(feature = "std")]
+impl From<std::io::Error> for SpError {
+    fn from(e: std::io::Error) -> Self {
+        SpError::ReadFailed(e)
+    }
+}
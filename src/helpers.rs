@@ -5,7 +5,7 @@ use crate::*;
 /// # Safety
 /// This function does no validation on the internal representation of V
 #[doc(hidden)]
-pub fn static_size_from_reader<V: SpRead, R: Read + ?Sized, const STATIC_SIZE: usize>(
+pub fn static_size_from_reader<V: SpRead, R: SpReader + ?Sized, const STATIC_SIZE: usize>(
     // Data source
     src: &mut R,
     // Parsing context
@@ -13,17 +13,641 @@ pub fn static_size_from_reader<V: SpRead, R: Read + ?Sized, const STATIC_SIZE: u
     // Data that has already been read from src. Use this first
     dst: &mut MaybeUninit<V>,
 ) -> Result<(), crate::SpError> {
+    ctx.check_bytes(STATIC_SIZE)?;
+
     let raw_bytes =
         unsafe { core::slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut u8, STATIC_SIZE) };
 
-    if let Err(e) = src.read_exact(raw_bytes) {
-        return Err(SpError::ReadFailed(e));
+    src.read_bytes(raw_bytes)?;
+
+    #[cfg(feature = "verbose")]
+    ::log::debug!("  read({STATIC_SIZE})");
+
+    ctx.cursor += STATIC_SIZE;
+
+    Ok(())
+}
+
+/// Splits `len` bytes off the front of `src`
+#[doc(hidden)]
+pub fn bytes_from_slice<'b>(
+    // Data source
+    src: &mut &'b [u8],
+    // Parsing context
+    ctx: &mut SpCtx,
+    // Number of bytes to consume
+    len: usize,
+) -> Result<&'b [u8], crate::SpError> {
+    ctx.check_bytes(len)?;
+    if src.len() < len {
+        return Err(SpError::NotEnoughSpace);
+    }
+    let (bytes, rest) = src.split_at(len);
+    *src = rest;
+
+    #[cfg(feature = "verbose")]
+    ::log::debug!("  read({len})");
+
+    ctx.cursor += len;
+
+    Ok(bytes)
+}
+
+/// Copies size_of::<V>() bytes from the slice into the MaybeUninit<V>
+///
+/// # Safety
+/// This function does no validation on the internal representation of V
+#[doc(hidden)]
+pub fn static_size_from_slice<V, const STATIC_SIZE: usize>(
+    // Data source
+    src: &mut &[u8],
+    // Parsing context
+    ctx: &mut SpCtx,
+    // Destination for the raw bytes
+    dst: &mut MaybeUninit<V>,
+) -> Result<(), crate::SpError> {
+    let bytes = bytes_from_slice(src, ctx, STATIC_SIZE)?;
+
+    unsafe {
+        core::ptr::copy_nonoverlapping(bytes.as_ptr(), dst.as_mut_ptr() as *mut u8, STATIC_SIZE);
     }
 
+    Ok(())
+}
+
+/// Puts size_of::<V>() bytes from an asynchronous reader into the MaybeUninit<V>
+///
+/// # Safety
+/// This function does no validation on the internal representation of V
+#[doc(hidden)]
+#[cfg(feature = "async")]
+pub async fn static_size_from_async_reader<
+    V,
+    R: SpAsyncReader + ?Sized,
+    const STATIC_SIZE: usize,
+>(
+    // Data source
+    src: &mut R,
+    // Parsing context
+    ctx: &mut SpCtx,
+    // Destination for the raw bytes
+    dst: &mut MaybeUninit<V>,
+) -> Result<(), crate::SpError> {
+    ctx.check_bytes(STATIC_SIZE)?;
+
+    let mut bytes = [0u8; STATIC_SIZE];
+    src.async_read_bytes(&mut bytes).await?;
+
     #[cfg(feature = "verbose")]
     ::log::debug!("  read({STATIC_SIZE})");
 
     ctx.cursor += STATIC_SIZE;
 
+    unsafe {
+        core::ptr::copy_nonoverlapping(bytes.as_ptr(), dst.as_mut_ptr() as *mut u8, STATIC_SIZE);
+    }
+
     Ok(())
 }
+
+/// Types that can be annotated with `#[sp(fixed_len = N)]` or `#[sp(eof)]`
+#[doc(hidden)]
+pub trait SpFixedLen: Sized {
+    /// Converts the bytes that are left once the padding is stripped
+    fn from_fixed_bytes(bytes: &[u8]) -> Result<Self, crate::SpError>;
+    /// Converts the rest of the input
+    fn from_byte_vec(bytes: alloc::vec::Vec<u8>) -> Result<Self, crate::SpError>;
+    /// Returns the bytes to write before the padding
+    fn fixed_bytes(&self) -> &[u8];
+}
+
+impl SpFixedLen for alloc::vec::Vec<u8> {
+    fn from_fixed_bytes(bytes: &[u8]) -> Result<Self, crate::SpError> {
+        Ok(bytes.to_vec())
+    }
+    fn from_byte_vec(bytes: alloc::vec::Vec<u8>) -> Result<Self, crate::SpError> {
+        Ok(bytes)
+    }
+    fn fixed_bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl SpFixedLen for alloc::string::String {
+    fn from_fixed_bytes(bytes: &[u8]) -> Result<Self, crate::SpError> {
+        match core::str::from_utf8(bytes) {
+            Ok(s) => Ok(s.into()),
+            Err(_) => Err(SpError::InvalidBytes),
+        }
+    }
+    fn from_byte_vec(bytes: alloc::vec::Vec<u8>) -> Result<Self, crate::SpError> {
+        alloc::string::String::from_utf8(bytes).map_err(|_| SpError::InvalidBytes)
+    }
+    fn fixed_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl SpFixedLen for alloc::ffi::CString {
+    fn from_fixed_bytes(bytes: &[u8]) -> Result<Self, crate::SpError> {
+        // Interior nul bytes are not allowed
+        alloc::ffi::CString::new(bytes).map_err(|_| SpError::InvalidBytes)
+    }
+    fn from_byte_vec(bytes: alloc::vec::Vec<u8>) -> Result<Self, crate::SpError> {
+        alloc::ffi::CString::new(bytes).map_err(|_| SpError::InvalidBytes)
+    }
+    fn fixed_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Strips the trailing `pad` bytes of a `#[sp(fixed_len)]` field
+#[doc(hidden)]
+pub fn from_fixed_bytes<T: SpFixedLen>(
+    bytes: &[u8],
+    pad: u8,
+    ctx: &mut SpCtx,
+) -> Result<T, crate::SpError> {
+    let len = bytes.iter().rposition(|b| *b != pad).map_or(0, |i| i + 1);
+    ctx.check_alloc(len)?;
+    T::from_fixed_bytes(&bytes[..len])
+}
+
+/// Pads the value of a `#[sp(fixed_len)]` field to `SIZE` bytes
+///
+/// Fails if the value ends with `pad` as those bytes would be stripped when reading it back
+#[doc(hidden)]
+pub fn to_fixed_bytes<T: SpFixedLen, const SIZE: usize>(
+    val: &T,
+    pad: u8,
+) -> Result<[u8; SIZE], crate::SpError> {
+    let bytes = val.fixed_bytes();
+    if bytes.len() > SIZE {
+        return Err(SpError::FixedLenOverflow {
+            max: SIZE,
+            len: bytes.len(),
+        });
+    }
+    if bytes.last() == Some(&pad) {
+        return Err(SpError::AmbiguousPadding);
+    }
+    let mut res = [pad; SIZE];
+    res[..bytes.len()].copy_from_slice(bytes);
+    Ok(res)
+}
+
+/// Initial number of bytes read at a time by `#[sp(eof)]` fields
+const EOF_CHUNK_SIZE: usize = 4096;
+
+/// Returns by how much a buffer of `len` bytes can grow.
+/// The buffer doubles in size without ever growing by more than MAX_ALLOC_SIZE at a time
+/// or past what is left of `max_alloc`
+fn grow_size(len: usize, min: usize, ctx: &SpCtx) -> usize {
+    let left = ctx.limits.max_alloc.saturating_sub(ctx.allocated);
+    // Leave room for at least one byte so that going over the limit is detected
+    len.clamp(min, MAX_ALLOC_SIZE).min(left).max(1)
+}
+
+/// Makes room for the next bytes of a `#[sp(eof)]` field.
+/// Only the bytes that are actually read are charged to `max_alloc`
+fn eof_grow(bytes: &mut alloc::vec::Vec<u8>, ctx: &SpCtx) {
+    let grow = grow_size(bytes.len(), EOF_CHUNK_SIZE, ctx);
+    bytes.resize(bytes.len() + grow, 0);
+}
+
+/// Reads the rest of the input into a `#[sp(eof)]` field
+#[doc(hidden)]
+pub fn eof_from_reader<T: SpFixedLen, R: SpReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+) -> Result<T, crate::SpError> {
+    let mut bytes = alloc::vec::Vec::new();
+    let mut len = 0;
+    loop {
+        if len == bytes.len() {
+            eof_grow(&mut bytes, ctx);
+        }
+        let n = src.read_some(&mut bytes[len..])?;
+        if n == 0 {
+            break;
+        }
+        ctx.check_bytes(n)?;
+        ctx.check_count(len + n)?;
+        ctx.check_alloc(n)?;
+        ctx.cursor += n;
+        len += n;
+    }
+    bytes.truncate(len);
+
+    #[cfg(feature = "verbose")]
+    ::log::debug!("  read({len})");
+
+    T::from_byte_vec(bytes)
+}
+
+/// Consumes the rest of the slice into a `#[sp(eof)]` field
+#[doc(hidden)]
+pub fn eof_from_slice<T: SpFixedLen>(
+    src: &mut &[u8],
+    ctx: &mut SpCtx,
+) -> Result<T, crate::SpError> {
+    ctx.check_count(src.len())?;
+    let bytes = bytes_from_slice(src, ctx, src.len())?;
+    ctx.check_alloc(bytes.len())?;
+    T::from_byte_vec(bytes.to_vec())
+}
+
+/// Reads the rest of the asynchronous input into a `#[sp(eof)]` field
+#[doc(hidden)]
+#[cfg(feature = "async")]
+pub async fn eof_from_async_reader<T: SpFixedLen, R: SpAsyncReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+) -> Result<T, crate::SpError> {
+    let mut bytes = alloc::vec::Vec::new();
+    let mut len = 0;
+    loop {
+        if len == bytes.len() {
+            eof_grow(&mut bytes, ctx);
+        }
+        let n = src.async_read_some(&mut bytes[len..]).await?;
+        if n == 0 {
+            break;
+        }
+        ctx.check_bytes(n)?;
+        ctx.check_count(len + n)?;
+        ctx.check_alloc(n)?;
+        ctx.cursor += n;
+        len += n;
+    }
+    bytes.truncate(len);
+
+    #[cfg(feature = "verbose")]
+    ::log::debug!("  read({len})");
+
+    T::from_byte_vec(bytes)
+}
+
+/// Initial number of bytes reserved at a time by `#[sp(until)]` strings
+const UNTIL_CHUNK_SIZE: usize = 64;
+
+/// Bytes of a `#[sp(until)]` string that are being read
+struct UntilBytes {
+    bytes: alloc::vec::Vec<u8>,
+    reserved: usize,
+}
+
+impl UntilBytes {
+    fn new() -> Self {
+        Self {
+            bytes: alloc::vec::Vec::new(),
+            reserved: 0,
+        }
+    }
+
+    /// Returns whether the bytes end with `term`
+    fn ends_with(&self, term: &[u8]) -> bool {
+        // Only compare the whole terminator once its last byte was read
+        self.bytes.last() == term.last() && self.bytes.ends_with(term)
+    }
+
+    /// Makes room for the next byte, charging `max_alloc` one chunk at a time
+    fn reserve(&mut self, ctx: &mut SpCtx) -> Result<(), crate::SpError> {
+        ctx.check_count(self.bytes.len() + 1)?;
+        if self.bytes.len() == self.reserved {
+            let grow = grow_size(self.reserved, UNTIL_CHUNK_SIZE, ctx);
+            ctx.check_alloc(grow)?;
+            self.bytes.reserve_exact(grow);
+            self.reserved += grow;
+        }
+        Ok(())
+    }
+
+    /// Returns the bytes without the terminator unless they `keep` it
+    fn finish<T: SpFixedLen>(
+        mut self,
+        ctx: &mut SpCtx,
+        term: &[u8],
+        keep: bool,
+    ) -> Result<T, crate::SpError> {
+        if !keep {
+            self.bytes.truncate(self.bytes.len() - term.len());
+        }
+        // Only the bytes that are kept stay charged, as when reading from a slice
+        ctx.allocated -= self.reserved - self.bytes.len();
+        T::from_byte_vec(self.bytes)
+    }
+}
+
+/// Reads the bytes of a `#[sp(until = "..")]` string up to its `term`inator
+///
+/// The terminator must not be consumed past its end so the bytes are read one at a time,
+/// unbuffered sources should be wrapped in a `BufReader`
+#[doc(hidden)]
+pub fn until_from_reader<T: SpFixedLen, R: SpReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+    term: &[u8],
+    keep: bool,
+) -> Result<T, crate::SpError> {
+    let mut bytes = UntilBytes::new();
+    while !bytes.ends_with(term) {
+        bytes.reserve(ctx)?;
+        let b = *<u8>::inner_from_reader(src, ctx, &mut MaybeUninit::uninit())?;
+        bytes.bytes.push(b);
+    }
+    bytes.finish(ctx, term, keep)
+}
+
+/// Splits the bytes of a `#[sp(until = "..")]` string up to its `term`inator off the slice
+#[doc(hidden)]
+pub fn until_from_slice<T: SpFixedLen>(
+    src: &mut &[u8],
+    ctx: &mut SpCtx,
+    term: &[u8],
+    keep: bool,
+) -> Result<T, crate::SpError> {
+    let len = match src.windows(term.len()).position(|w| w == term) {
+        Some(pos) => pos + term.len(),
+        // The terminator is missing
+        None => return Err(SpError::NotEnoughSpace),
+    };
+    ctx.check_count(len)?;
+    let mut bytes = bytes_from_slice(src, ctx, len)?;
+    if !keep {
+        bytes = &bytes[..len - term.len()];
+    }
+    ctx.check_alloc(bytes.len())?;
+    T::from_byte_vec(bytes.to_vec())
+}
+
+/// Reads the bytes of a `#[sp(until = "..")]` string up to its `term`inator from an asynchronous reader
+///
+/// The bytes are read one at a time, unbuffered streams should be wrapped in a `BufReader`
+#[doc(hidden)]
+#[cfg(feature = "async")]
+pub async fn until_from_async_reader<T: SpFixedLen, R: SpAsyncReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+    term: &[u8],
+    keep: bool,
+) -> Result<T, crate::SpError> {
+    let mut bytes = UntilBytes::new();
+    while !bytes.ends_with(term) {
+        bytes.reserve(ctx)?;
+        let b = <u8>::inner_from_async_reader(src, ctx).await?;
+        bytes.bytes.push(b);
+    }
+    bytes.finish(ctx, term, keep)
+}
+
+/// Returns the bytes to write for a `#[sp(until = "..")]` string followed by its `term`inator
+///
+/// Fails if the terminator would be found before the end of the value when reading it back
+#[doc(hidden)]
+pub fn until_to_bytes<T: SpFixedLen>(
+    val: &T,
+    term: &[u8],
+    keep: bool,
+) -> Result<alloc::vec::Vec<u8>, crate::SpError> {
+    let mut bytes = val.fixed_bytes().to_vec();
+    // A kept terminator is already part of the value
+    if !(keep && bytes.ends_with(term)) {
+        bytes.extend_from_slice(term);
+    }
+    match bytes.windows(term.len()).position(|w| w == term) {
+        Some(pos) if pos + term.len() == bytes.len() => Ok(bytes),
+        _ => Err(SpError::AmbiguousTerminator),
+    }
+}
+
+/// Fails with [SpError::BadMagic] unless the bytes of a `magic` or `const` match the expected ones
+#[doc(hidden)]
+pub fn check_magic(found: &[u8], expected: &[u8]) -> Result<(), crate::SpError> {
+    if found != expected {
+        return Err(SpError::BadMagic {
+            expected: expected.to_vec(),
+            found: found.to_vec(),
+        });
+    }
+    Ok(())
+}
+
+/// Fails with [SpError::BadChecksum] unless the value of a `checksum` field matches the computed one
+#[doc(hidden)]
+pub fn check_checksum<T: Copy + PartialEq + Into<u64>>(
+    found: T,
+    expected: T,
+) -> Result<(), crate::SpError> {
+    if found != expected {
+        return Err(SpError::BadChecksum {
+            expected: expected.into(),
+            found: found.into(),
+        });
+    }
+    Ok(())
+}
+
+/// Size of the chunks used to skip or write padding
+const PAD_CHUNK_SIZE: usize = 64;
+
+/// Number of bytes needed to move `cursor` to a multiple of `align`
+#[doc(hidden)]
+pub fn align_padding(cursor: usize, align: usize) -> usize {
+    (align - cursor % align) % align
+}
+
+/// Makes sure skipped padding only contains the `pad` byte when `strict`
+fn check_padding(bytes: &[u8], pad: u8, strict: bool) -> Result<(), crate::SpError> {
+    match bytes.iter().find(|b| **b != pad) {
+        Some(b) if strict => Err(SpError::BadPadding {
+            expected: pad,
+            found: *b,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Skips `num_bytes` of `#[sp(pad_before, pad_after, align)]` padding
+#[doc(hidden)]
+pub fn pad_from_reader<R: SpReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+    num_bytes: usize,
+    pad: u8,
+    strict: bool,
+) -> Result<(), crate::SpError> {
+    ctx.check_bytes(num_bytes)?;
+    let mut buf = [0u8; PAD_CHUNK_SIZE];
+    let mut left = num_bytes;
+    while left > 0 {
+        let chunk = &mut buf[..core::cmp::min(left, PAD_CHUNK_SIZE)];
+        src.read_bytes(chunk)?;
+        ctx.cursor += chunk.len();
+        left -= chunk.len();
+        check_padding(chunk, pad, strict)?;
+    }
+
+    #[cfg(feature = "verbose")]
+    ::log::debug!("  skip({num_bytes})");
+
+    Ok(())
+}
+
+/// Skips `num_bytes` of `#[sp(pad_before, pad_after, align)]` padding from the slice
+#[doc(hidden)]
+pub fn pad_from_slice(
+    src: &mut &[u8],
+    ctx: &mut SpCtx,
+    num_bytes: usize,
+    pad: u8,
+    strict: bool,
+) -> Result<(), crate::SpError> {
+    let bytes = bytes_from_slice(src, ctx, num_bytes)?;
+    check_padding(bytes, pad, strict)
+}
+
+/// Skips `num_bytes` of `#[sp(pad_before, pad_after, align)]` padding from the asynchronous reader
+#[doc(hidden)]
+#[cfg(feature = "async")]
+pub async fn pad_from_async_reader<R: SpAsyncReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+    num_bytes: usize,
+    pad: u8,
+    strict: bool,
+) -> Result<(), crate::SpError> {
+    ctx.check_bytes(num_bytes)?;
+    let mut buf = [0u8; PAD_CHUNK_SIZE];
+    let mut left = num_bytes;
+    while left > 0 {
+        let chunk = &mut buf[..core::cmp::min(left, PAD_CHUNK_SIZE)];
+        src.async_read_bytes(chunk).await?;
+        ctx.cursor += chunk.len();
+        left -= chunk.len();
+        check_padding(chunk, pad, strict)?;
+    }
+
+    #[cfg(feature = "verbose")]
+    ::log::debug!("  skip({num_bytes})");
+
+    Ok(())
+}
+
+/// Writes `num_bytes` of `pad` for `#[sp(pad_before, pad_after, align)]`
+#[doc(hidden)]
+pub fn pad_to_writer<W: SpWriter + ?Sized>(
+    ctx: &mut SpCtx,
+    dst: &mut W,
+    num_bytes: usize,
+    pad: u8,
+) -> Result<usize, crate::SpError> {
+    let buf = [pad; PAD_CHUNK_SIZE];
+    let mut left = num_bytes;
+    while left > 0 {
+        let chunk = &buf[..core::cmp::min(left, PAD_CHUNK_SIZE)];
+        dst.write_bytes(chunk)?;
+        ctx.cursor += chunk.len();
+        left -= chunk.len();
+    }
+    Ok(num_bytes)
+}
+
+/// Writes `num_bytes` of `pad` for `#[sp(pad_before, pad_after, align)]` into the asynchronous writer
+#[doc(hidden)]
+#[cfg(feature = "async")]
+pub async fn pad_to_async_writer<W: SpAsyncWriter + ?Sized>(
+    ctx: &mut SpCtx,
+    dst: &mut W,
+    num_bytes: usize,
+    pad: u8,
+) -> Result<usize, crate::SpError> {
+    let buf = [pad; PAD_CHUNK_SIZE];
+    let mut left = num_bytes;
+    while left > 0 {
+        let chunk = &buf[..core::cmp::min(left, PAD_CHUNK_SIZE)];
+        dst.async_write_bytes(chunk).await?;
+        ctx.cursor += chunk.len();
+        left -= chunk.len();
+    }
+    Ok(num_bytes)
+}
+
+/// Collections that can be annotated with `#[sp(byte_len = "x")]` or `#[sp(until = "x")]`
+#[doc(hidden)]
+pub trait SpByteLen: Default {
+    /// The type of each item
+    type Item;
+    /// Adds an item at the end of the collection
+    fn push_item(&mut self, item: Self::Item);
+}
+
+impl<T> SpByteLen for alloc::vec::Vec<T> {
+    type Item = T;
+    fn push_item(&mut self, item: T) {
+        self.push(item);
+    }
+}
+
+impl<T> SpByteLen for alloc::collections::VecDeque<T> {
+    type Item = T;
+    fn push_item(&mut self, item: T) {
+        self.push_back(item);
+    }
+}
+
+impl<T> SpByteLen for alloc::collections::LinkedList<T> {
+    type Item = T;
+    fn push_item(&mut self, item: T) {
+        self.push_back(item);
+    }
+}
+
+/// Integer arithmetic used by `len` and `calc` expressions
+///
+/// Every operation returns `None` instead of overflowing or dividing by zero.
+#[doc(hidden)]
+pub trait SpCheckedOps: Sized {
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+    fn checked_shl(self, rhs: u32) -> Option<Self>;
+    fn checked_shr(self, rhs: u32) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+}
+
+macro_rules! checked_ops {
+    ($($typ:ty),+) => {
+        $(
+        impl SpCheckedOps for $typ {
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$typ>::checked_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$typ>::checked_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$typ>::checked_mul(self, rhs)
+            }
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                <$typ>::checked_div(self, rhs)
+            }
+            fn checked_rem(self, rhs: Self) -> Option<Self> {
+                <$typ>::checked_rem(self, rhs)
+            }
+            fn checked_shl(self, rhs: u32) -> Option<Self> {
+                <$typ>::checked_shl(self, rhs)
+            }
+            fn checked_shr(self, rhs: u32) -> Option<Self> {
+                <$typ>::checked_shr(self, rhs)
+            }
+            fn checked_neg(self) -> Option<Self> {
+                <$typ>::checked_neg(self)
+            }
+        }
+        )+
+    };
+}
+checked_ops!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
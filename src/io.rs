@@ -0,0 +1,250 @@
+use crate::SpError;
+#[cfg(feature = "async")]
+use core::future::Future;
+
+/// A source of bytes for [SpRead](crate::SpRead)
+///
+/// With the `std` feature, this is implemented for every [std::io::Read].
+pub trait SpReader {
+    /// Fills `buf` with the next bytes from the source
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SpError>;
+
+    /// Reads up to `buf.len()` bytes and returns how many were read, 0 meaning that the source is exhausted
+    ///
+    /// Used by `#[sp(eof)]` fields. The default implementation always fills `buf`, sources that can tell
+    /// where they end should override it.
+    fn read_some(&mut self, buf: &mut [u8]) -> Result<usize, SpError> {
+        self.read_bytes(buf)?;
+        Ok(buf.len())
+    }
+}
+
+/// A destination for the bytes produced by [SpWrite](crate::SpWrite)
+///
+/// With the `std` feature, this is implemented for every [std::io::Write].
+pub trait SpWriter {
+    /// Writes all of `buf` into the destination
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError>;
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read + ?Sized> SpReader for R {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SpError> {
+        match self.read_exact(buf) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(SpError::ReadFailed(e)),
+        }
+    }
+
+    fn read_some(&mut self, buf: &mut [u8]) -> Result<usize, SpError> {
+        loop {
+            match self.read(buf) {
+                Ok(n) => return Ok(n),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(SpError::ReadFailed(e)),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write + ?Sized> SpWriter for W {
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError> {
+        match self.write_all(buf) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(SpError::from_write_error(e)),
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl SpReader for &[u8] {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SpError> {
+        if self.len() < buf.len() {
+            return Err(SpError::NotEnoughSpace);
+        }
+        let (bytes, rest) = self.split_at(buf.len());
+        buf.copy_from_slice(bytes);
+        *self = rest;
+        Ok(())
+    }
+
+    fn read_some(&mut self, buf: &mut [u8]) -> Result<usize, SpError> {
+        let len = core::cmp::min(self.len(), buf.len());
+        let (bytes, rest) = self.split_at(len);
+        buf[..len].copy_from_slice(bytes);
+        *self = rest;
+        Ok(len)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl SpWriter for alloc::vec::Vec<u8> {
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl SpWriter for &mut [u8] {
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError> {
+        if self.len() < buf.len() {
+            return Err(SpError::NotEnoughSpace);
+        }
+        let (dst, rest) = core::mem::take(self).split_at_mut(buf.len());
+        dst.copy_from_slice(buf);
+        *self = rest;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<R: SpReader + ?Sized> SpReader for &mut R {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SpError> {
+        (**self).read_bytes(buf)
+    }
+
+    fn read_some(&mut self, buf: &mut [u8]) -> Result<usize, SpError> {
+        (**self).read_some(buf)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<W: SpWriter + ?Sized> SpWriter for &mut W {
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError> {
+        (**self).write_bytes(buf)
+    }
+}
+
+/// A destination that discards every byte
+///
+/// Used to compute the encoded size of a value
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SpSink;
+
+impl SpWriter for SpSink {
+    fn write_bytes(&mut self, _buf: &[u8]) -> Result<(), SpError> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl SpAsyncWriter for SpSink {
+    async fn async_write_bytes(&mut self, _buf: &[u8]) -> Result<(), SpError> {
+        Ok(())
+    }
+}
+
+/// An asynchronous source of bytes for [SpAsyncRead](crate::SpAsyncRead)
+///
+/// With the `tokio` feature, this is implemented for every [tokio::io::AsyncRead].
+#[cfg(feature = "async")]
+pub trait SpAsyncReader {
+    /// Fills `buf` with the next bytes from the source
+    fn async_read_bytes(&mut self, buf: &mut [u8]) -> impl Future<Output = Result<(), SpError>>;
+
+    /// Reads up to `buf.len()` bytes and returns how many were read, 0 meaning that the source is exhausted
+    ///
+    /// Used by `#[sp(eof)]` fields. The default implementation always fills `buf`, sources that can tell
+    /// where they end should override it.
+    fn async_read_some(&mut self, buf: &mut [u8]) -> impl Future<Output = Result<usize, SpError>> {
+        async move {
+            self.async_read_bytes(buf).await?;
+            Ok(buf.len())
+        }
+    }
+}
+
+/// An asynchronous destination for the bytes produced by [SpAsyncWrite](crate::SpAsyncWrite)
+///
+/// With the `tokio` feature, this is implemented for every [tokio::io::AsyncWrite].
+#[cfg(feature = "async")]
+pub trait SpAsyncWriter {
+    /// Writes all of `buf` into the destination
+    fn async_write_bytes(&mut self, buf: &[u8]) -> impl Future<Output = Result<(), SpError>>;
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin + ?Sized> SpAsyncReader for R {
+    async fn async_read_bytes(&mut self, buf: &mut [u8]) -> Result<(), SpError> {
+        match tokio::io::AsyncReadExt::read_exact(self, buf).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(SpError::ReadFailed(e)),
+        }
+    }
+
+    async fn async_read_some(&mut self, buf: &mut [u8]) -> Result<usize, SpError> {
+        match tokio::io::AsyncReadExt::read(self, buf).await {
+            Ok(n) => Ok(n),
+            Err(e) => Err(SpError::ReadFailed(e)),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<W: tokio::io::AsyncWrite + Unpin + ?Sized> SpAsyncWriter for W {
+    async fn async_write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError> {
+        match tokio::io::AsyncWriteExt::write_all(self, buf).await {
+            Ok(()) => Ok(()),
+            Err(e) => Err(SpError::from_write_error(e)),
+        }
+    }
+}
+
+/// Wraps a [futures_io] stream to implement [SpAsyncReader] and [SpAsyncWriter]
+///
+/// A wrapper is used as a stream could implement both the tokio and the futures-io traits.
+#[cfg(feature = "futures-io")]
+pub struct FuturesIo<T>(pub T);
+
+#[cfg(feature = "futures-io")]
+impl<R: futures_io::AsyncRead + Unpin> SpAsyncReader for FuturesIo<R> {
+    async fn async_read_bytes(&mut self, mut buf: &mut [u8]) -> Result<(), SpError> {
+        while !buf.is_empty() {
+            let n = core::future::poll_fn(|cx| core::pin::Pin::new(&mut self.0).poll_read(cx, buf))
+                .await;
+            match n {
+                Ok(0) => {
+                    return Err(SpError::ReadFailed(
+                        std::io::ErrorKind::UnexpectedEof.into(),
+                    ))
+                }
+                Ok(n) => buf = &mut buf[n..],
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(SpError::ReadFailed(e)),
+            }
+        }
+        Ok(())
+    }
+
+    async fn async_read_some(&mut self, buf: &mut [u8]) -> Result<usize, SpError> {
+        loop {
+            let n = core::future::poll_fn(|cx| core::pin::Pin::new(&mut self.0).poll_read(cx, buf))
+                .await;
+            match n {
+                Ok(n) => return Ok(n),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(SpError::ReadFailed(e)),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<W: futures_io::AsyncWrite + Unpin> SpAsyncWriter for FuturesIo<W> {
+    async fn async_write_bytes(&mut self, mut buf: &[u8]) -> Result<(), SpError> {
+        while !buf.is_empty() {
+            let n =
+                core::future::poll_fn(|cx| core::pin::Pin::new(&mut self.0).poll_write(cx, buf))
+                    .await;
+            match n {
+                Ok(0) => return Err(SpError::NotEnoughSpace),
+                Ok(n) => buf = &buf[n..],
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(SpError::from_write_error(e)),
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,8 @@
-use std::{
-    io::{Read, Write},
-    mem::MaybeUninit,
-};
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use core::mem::MaybeUninit;
 
 #[cfg(feature = "verbose")]
 pub use log::debug;
@@ -9,19 +10,37 @@ pub use log::debug;
 mod error;
 pub use error::*;
 
+mod io;
+pub use io::*;
+
 mod default_impls;
-pub use default_impls::*;
+
+mod varint;
+pub use varint::*;
+
+mod bits;
+pub use bits::*;
+
+mod checksum;
+pub use checksum::*;
 
 mod helpers;
 pub use helpers::*;
 
+#[cfg(feature = "std")]
+mod seek;
+#[cfg(feature = "std")]
+pub use seek::*;
+
 pub use simple_parse_derive::*;
 
 const DEFAULT_IS_LITTLE_ENDIAN: bool = true;
 
 /// A context passed around [SpRead] and [SpWrite] functions
+#[derive(Debug, Clone)]
 pub struct SpCtx {
-    /// How many bytes have been read/written so far
+    /// How many bytes have been read/written so far.
+    /// After an `#[sp(offset)]` seek, this is the position from where reading/writing started
     pub cursor: usize,
     /// This value should only be checked inside custom validators (which get called for both Read & Write)
     /// Its contents are considered invalid otherwise
@@ -30,6 +49,22 @@ pub struct SpCtx {
     pub is_little_endian: bool,
     /// If a dynamically sized Self uses an external `len` field, and what its contents are
     pub len: Option<usize>,
+    /// Type of the count prefixed to dynamically sized types that have no external `len` field
+    pub len_type: SpLenType,
+    /// Partially read/written byte of `#[sp(bits)]` fields
+    pub bits: SpBitCursor,
+    /// Limits enforced while reading
+    pub limits: SpLimits,
+    /// How many bytes have been allocated by collections so far
+    pub allocated: usize,
+    /// How many derived types are currently being read
+    pub depth: usize,
+    /// Value of `cursor` at the start of the innermost type derived with `SpSeekRead` or `SpSeekWrite`.
+    /// `#[sp(offset_from = "struct")]` offsets are relative to it
+    pub struct_start: usize,
+    /// How far `#[sp(offset)]` seeks moved `cursor` backwards while reading.
+    /// Those bytes can be read again, so they still count towards `limits.max_bytes`
+    pub rewound: usize,
 }
 impl Default for SpCtx {
     fn default() -> Self {
@@ -38,9 +73,129 @@ impl Default for SpCtx {
             is_reading: true,
             is_little_endian: DEFAULT_IS_LITTLE_ENDIAN,
             len: None,
+            len_type: SpLenType::default(),
+            bits: SpBitCursor::default(),
+            limits: SpLimits::default(),
+            allocated: 0,
+            depth: 0,
+            struct_start: 0,
+            rewound: 0,
         }
     }
 }
+impl SpCtx {
+    /// Creates a context that enforces `limits` while reading
+    pub fn with_limits(limits: SpLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Makes sure `num_bytes` more bytes can be consumed from the input
+    pub fn check_bytes(&self, num_bytes: usize) -> Result<(), SpError> {
+        let requested = self
+            .cursor
+            .saturating_add(self.rewound)
+            .saturating_add(num_bytes);
+        if requested > self.limits.max_bytes {
+            return Err(SpError::LimitExceeded {
+                limit: SpLimit::Bytes,
+                max: self.limits.max_bytes,
+                requested,
+            });
+        }
+        Ok(())
+    }
+
+    /// Makes sure a collection can hold `count` elements
+    pub fn check_count(&self, count: usize) -> Result<(), SpError> {
+        if count > self.limits.max_count {
+            return Err(SpError::LimitExceeded {
+                limit: SpLimit::Count,
+                max: self.limits.max_count,
+                requested: count,
+            });
+        }
+        Ok(())
+    }
+
+    /// Records an allocation of `num_bytes` if it fits within the limits
+    pub fn check_alloc(&mut self, num_bytes: usize) -> Result<(), SpError> {
+        let requested = self.allocated.saturating_add(num_bytes);
+        if requested > self.limits.max_alloc {
+            return Err(SpError::LimitExceeded {
+                limit: SpLimit::Alloc,
+                max: self.limits.max_alloc,
+                requested,
+            });
+        }
+        self.allocated = requested;
+        Ok(())
+    }
+
+    /// Called when starting to read a nested type
+    pub fn enter(&mut self) -> Result<(), SpError> {
+        if self.depth >= self.limits.max_depth {
+            return Err(SpError::LimitExceeded {
+                limit: SpLimit::Depth,
+                max: self.limits.max_depth,
+                requested: self.depth + 1,
+            });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Called once a nested type has been read
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+}
+
+/// Limits that protect against malicious/malformed inputs
+///
+/// Every limit is disabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpLimits {
+    /// Maximum number of bytes consumed from the input
+    pub max_bytes: usize,
+    /// Maximum number of elements in a single collection
+    pub max_count: usize,
+    /// Maximum number of bytes allocated by all collections
+    pub max_alloc: usize,
+    /// Maximum nesting depth of derived types
+    pub max_depth: usize,
+}
+impl Default for SpLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: usize::MAX,
+            max_count: usize::MAX,
+            max_alloc: usize::MAX,
+            max_depth: usize::MAX,
+        }
+    }
+}
+
+/// Integer type used as the count prefix of dynamically sized types
+///
+/// Set through `#[sp(len_type = "u16")]` on a field or a whole struct/enum. The setting also applies to
+/// nested types, e.g. both counts of a `Vec<String>`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SpLenType {
+    /// `u8`
+    U8,
+    /// `u16`
+    U16,
+    /// `u32`
+    #[default]
+    U32,
+    /// `u64`
+    U64,
+    /// [VarU64]
+    Varint,
+}
 
 #[doc(hidden)]
 /// This type is used for dynamically sized type's default implementations
@@ -52,7 +207,7 @@ pub type DefaultCountType = u32;
 /// MAX_ALLOC_SIZE will be read at a time instead of allocating INT_MAX bytes in one go.
 pub const MAX_ALLOC_SIZE: usize = 4 * 1024 * 1024; // 4 MB
 
-/// Parses untrusted bytes from a [Reader](std::io::Read) into a `Self`
+/// Parses untrusted bytes from a [Reader](SpReader) into a `Self`
 ///
 /// This trait is most usefull when the bytes are coming from some kind of IO stream.
 /// When possible, it is recommend to use [SpReadRaw] instead for better performance.
@@ -64,8 +219,8 @@ pub trait SpRead: Sized {
     /// Marks types that have the same byte representation on the wire and in memory
     const IS_SAFE_REPR: bool = false;
 
-    /// Converts bytes from a `&mut Read` into `Self`
-    fn from_reader<'a, R: Read + ?Sized>(
+    /// Converts bytes from a `&mut SpReader` into `Self`
+    fn from_reader<'a, R: SpReader + ?Sized>(
         src: &mut R,
         dst: &'a mut MaybeUninit<Self>,
     ) -> Result<&'a mut Self, crate::SpError> {
@@ -78,8 +233,8 @@ pub trait SpRead: Sized {
         Ok(v)
     }
 
-    /// Parses bytes from a [Reader](std::io::Read) into `dst` and returns a valid
-    fn inner_from_reader<'a, R: Read + ?Sized>(
+    /// Parses bytes from a [Reader](SpReader) into `dst` and returns a valid
+    fn inner_from_reader<'a, R: SpReader + ?Sized>(
         src: &mut R,
         ctx: &mut SpCtx,
         dst: &'a mut MaybeUninit<Self>,
@@ -94,17 +249,114 @@ pub trait SpRead: Sized {
     }
 }
 
-/// Writes the binary representation of `Self` into a [Writer](std::io::Write)
+/// Parses untrusted bytes from a byte slice into a `Self`
+///
+/// Unlike [SpRead], implementations are free to borrow from the input which allows
+/// types like `&'b str`, `&'b [u8]` or `&'b CStr` to be parsed without any copies.
+/// The slice is advanced past the consumed bytes.
+pub trait SpReadRaw<'b>: Sized {
+    #[doc(hidden)]
+    /// Marks types that have the same byte representation on the wire and in memory
+    const IS_SAFE_REPR: bool = false;
+
+    /// Converts bytes from a `&mut &[u8]` into `Self`
+    fn from_slice<'a>(
+        src: &mut &'b [u8],
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut ctx = SpCtx::default();
+
+        let v = Self::inner_from_slice(src, &mut ctx, dst)?;
+        #[cfg(feature = "verbose")]
+        ::log::debug!("  total : {} bytes", ctx.cursor);
+
+        Ok(v)
+    }
+
+    /// Parses bytes from a byte slice into `dst` and returns a valid
+    fn inner_from_slice<'a>(
+        src: &mut &'b [u8],
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError>;
+}
+
+/// Parses untrusted bytes from an [asynchronous reader](SpAsyncReader) into a `Self`
+///
+/// Unlike [SpRead], `Self` is returned by value as the future owns the partially parsed data.
+#[cfg(feature = "async")]
+pub trait SpAsyncRead: Sized {
+    #[doc(hidden)]
+    /// Marks types that have the same byte representation on the wire and in memory
+    const IS_SAFE_REPR: bool = false;
+
+    /// Converts bytes from a `&mut SpAsyncReader` into `Self`
+    fn from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+    ) -> impl core::future::Future<Output = Result<Self, crate::SpError>> {
+        async move {
+            let mut ctx = SpCtx::default();
+
+            let v = Self::inner_from_async_reader(src, &mut ctx).await?;
+            #[cfg(feature = "verbose")]
+            ::log::debug!("  total : {} bytes", ctx.cursor);
+
+            Ok(v)
+        }
+    }
+
+    /// Parses bytes from an [asynchronous reader](SpAsyncReader) into `Self`
+    fn inner_from_async_reader<R: SpAsyncReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+    ) -> impl core::future::Future<Output = Result<Self, crate::SpError>>;
+
+    #[doc(hidden)]
+    unsafe fn validate_contents<'a>(
+        _ctx: &mut SpCtx,
+        _dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        panic!("validate_content internal api should not be used !");
+    }
+}
+
+/// Writes the binary representation of `Self` into an [asynchronous writer](SpAsyncWriter)
+#[cfg(feature = "async")]
+pub trait SpAsyncWrite {
+    /// Writes the byte representation for Self into a `&mut SpAsyncWriter` with control over endianness
+    fn inner_to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> impl core::future::Future<Output = Result<usize, crate::SpError>>;
+
+    /// Writes the byte representation for Self into a `&mut SpAsyncWriter`
+    fn to_async_writer<W: SpAsyncWriter + ?Sized>(
+        &self,
+        dst: &mut W,
+    ) -> impl core::future::Future<Output = Result<usize, crate::SpError>> {
+        async move {
+            let mut ctx = SpCtx::default();
+            let r = self.inner_to_async_writer(&mut ctx, dst).await;
+            #[cfg(feature = "verbose")]
+            ::log::debug!("  Wrote {} bytes", ctx.cursor);
+
+            r
+        }
+    }
+}
+
+/// Writes the binary representation of `Self` into a [Writer](SpWriter)
 pub trait SpWrite {
-    /// Writes the byte representation for Self into a `&mut Write` with control over endianness
-    fn inner_to_writer<W: Write + ?Sized>(
+    /// Writes the byte representation for Self into a `&mut SpWriter` with control over endianness
+    fn inner_to_writer<W: SpWriter + ?Sized>(
         &self,
         ctx: &mut SpCtx,
         dst: &mut W,
     ) -> Result<usize, crate::SpError>;
 
-    /// Writes the byte representation for Self into a `&mut Write`
-    fn to_writer<W: Write + ?Sized>(&self, dst: &mut W) -> Result<usize, crate::SpError> {
+    /// Writes the byte representation for Self into a `&mut SpWriter`
+    fn to_writer<W: SpWriter + ?Sized>(&self, dst: &mut W) -> Result<usize, crate::SpError> {
         let mut ctx = SpCtx::default();
         let r = self.inner_to_writer(&mut ctx, dst);
         #[cfg(feature = "verbose")]
@@ -0,0 +1,188 @@
+use core::mem::MaybeUninit;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::*;
+
+/// A [SpReader] that can move to any position of its input
+///
+/// This is implemented for every [std::io::Read] + [std::io::Seek].
+pub trait SpSeekReader: SpReader {
+    /// Returns the current position from the start of the input
+    fn seek_position(&mut self) -> Result<u64, SpError>;
+    /// Moves to `pos` bytes from the start of the input
+    fn seek_to(&mut self, pos: u64) -> Result<(), SpError>;
+}
+
+/// A [SpWriter] that can move to any position of its output
+///
+/// This is implemented for every [std::io::Write] + [std::io::Seek].
+pub trait SpSeekWriter: SpWriter {
+    /// Returns the current position from the start of the output
+    fn seek_position(&mut self) -> Result<u64, SpError>;
+    /// Moves to `pos` bytes from the start of the output
+    fn seek_to(&mut self, pos: u64) -> Result<(), SpError>;
+}
+
+impl<R: Read + Seek + ?Sized> SpSeekReader for R {
+    fn seek_position(&mut self) -> Result<u64, SpError> {
+        self.stream_position().map_err(SpError::ReadFailed)
+    }
+
+    fn seek_to(&mut self, pos: u64) -> Result<(), SpError> {
+        match self.seek(SeekFrom::Start(pos)) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(SpError::ReadFailed(e)),
+        }
+    }
+}
+
+impl<W: Write + Seek + ?Sized> SpSeekWriter for W {
+    fn seek_position(&mut self) -> Result<u64, SpError> {
+        self.stream_position().map_err(SpError::WriteFailed)
+    }
+
+    fn seek_to(&mut self, pos: u64) -> Result<(), SpError> {
+        match self.seek(SeekFrom::Start(pos)) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(SpError::WriteFailed(e)),
+        }
+    }
+}
+
+impl<R: SpSeekReader + ?Sized, C: SpChecksum> SpSeekReader for SpChecksumTap<'_, R, C> {
+    fn seek_position(&mut self) -> Result<u64, SpError> {
+        self.inner_mut().seek_position()
+    }
+
+    fn seek_to(&mut self, pos: u64) -> Result<(), SpError> {
+        self.inner_mut().seek_to(pos)
+    }
+}
+
+impl<W: SpSeekWriter + ?Sized, C: SpChecksum> SpSeekWriter for SpChecksumTap<'_, W, C> {
+    fn seek_position(&mut self) -> Result<u64, SpError> {
+        self.inner_mut().seek_position()
+    }
+
+    fn seek_to(&mut self, pos: u64) -> Result<(), SpError> {
+        self.inner_mut().seek_to(pos)
+    }
+}
+
+/// Parses untrusted bytes from a [seekable reader](SpSeekReader) into a `Self`
+///
+/// Types with `#[sp(offset)]` fields have to move around their input and derive this trait
+/// instead of [SpRead]. Every [SpRead] type also implements it, which means that containers such as
+/// `Vec<T>` or `Option<T>` cannot hold a type that only implements [SpSeekRead].
+pub trait SpSeekRead: Sized {
+    /// Converts bytes from a `&mut SpSeekReader` into `Self`
+    fn from_seek_reader<'a, R: SpSeekReader + ?Sized>(
+        src: &mut R,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        let mut ctx = SpCtx::default();
+
+        let v = Self::inner_from_seek_reader(src, &mut ctx, dst)?;
+        #[cfg(feature = "verbose")]
+        ::log::debug!("  total : {} bytes", ctx.cursor);
+
+        Ok(v)
+    }
+
+    /// Parses bytes from a [seekable reader](SpSeekReader) into `dst` and returns a valid
+    fn inner_from_seek_reader<'a, R: SpSeekReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError>;
+}
+
+impl<T: SpRead> SpSeekRead for T {
+    fn inner_from_seek_reader<'a, R: SpSeekReader + ?Sized>(
+        src: &mut R,
+        ctx: &mut SpCtx,
+        dst: &'a mut MaybeUninit<Self>,
+    ) -> Result<&'a mut Self, crate::SpError> {
+        T::inner_from_reader(src, ctx, dst)
+    }
+}
+
+/// Writes the binary representation of `Self` into a [seekable writer](SpSeekWriter)
+///
+/// Types with `#[sp(offset)]` fields have to move around their output and derive this trait
+/// instead of [SpWrite]. Every [SpWrite] type also implements it, which means that containers such as
+/// `Vec<T>` or `Option<T>` cannot hold a type that only implements [SpSeekWrite].
+pub trait SpSeekWrite {
+    /// Writes the byte representation for Self into a `&mut SpSeekWriter` with control over endianness
+    fn inner_to_seek_writer<W: SpSeekWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError>;
+
+    /// Writes the byte representation for Self into a `&mut SpSeekWriter`
+    fn to_seek_writer<W: SpSeekWriter + ?Sized>(
+        &self,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        let mut ctx = SpCtx::default();
+        let r = self.inner_to_seek_writer(&mut ctx, dst);
+        #[cfg(feature = "verbose")]
+        ::log::debug!("  Wrote {} bytes", ctx.cursor);
+
+        r
+    }
+}
+
+impl<T: SpWrite + ?Sized> SpSeekWrite for T {
+    fn inner_to_seek_writer<W: SpSeekWriter + ?Sized>(
+        &self,
+        ctx: &mut SpCtx,
+        dst: &mut W,
+    ) -> Result<usize, crate::SpError> {
+        self.inner_to_writer(ctx, dst)
+    }
+}
+
+/// Moves the reader to `offset` bytes from where reading started and returns the previous `cursor`
+#[doc(hidden)]
+pub fn seek_reader_to<R: SpSeekReader + ?Sized>(
+    src: &mut R,
+    ctx: &mut SpCtx,
+    offset: usize,
+) -> Result<usize, crate::SpError> {
+    // The input was already positioned before the first byte was read
+    let start = src
+        .seek_position()?
+        .checked_sub(ctx.cursor as u64)
+        .ok_or(SpError::InvalidBytes)?;
+    src.seek_to(start + offset as u64)?;
+
+    #[cfg(feature = "verbose")]
+    ::log::debug!("  seek({offset})");
+
+    // Seeking back must not reset the byte limit
+    ctx.rewound = ctx
+        .rewound
+        .saturating_add(ctx.cursor.saturating_sub(offset));
+    Ok(core::mem::replace(&mut ctx.cursor, offset))
+}
+
+/// Moves the writer to `offset` bytes from where writing started and returns the previous `cursor`
+#[doc(hidden)]
+pub fn seek_writer_to<W: SpSeekWriter + ?Sized>(
+    dst: &mut W,
+    ctx: &mut SpCtx,
+    offset: usize,
+) -> Result<usize, crate::SpError> {
+    let start = dst
+        .seek_position()?
+        .checked_sub(ctx.cursor as u64)
+        .ok_or(SpError::InvalidBytes)?;
+    dst.seek_to(start + offset as u64)?;
+
+    #[cfg(feature = "verbose")]
+    ::log::debug!("  seek({offset})");
+
+    Ok(core::mem::replace(&mut ctx.cursor, offset))
+}
@@ -0,0 +1,135 @@
+use core::convert::TryFrom;
+
+use crate::SpError;
+
+/// Maximum number of bytes in a LEB128 encoded u64
+pub(crate) const MAX_VARINT_LEN: usize = 10;
+
+/// An unsigned integer encoded as LEB128
+///
+/// Each byte holds 7 bits of the value starting with the least significant bits. The high bit
+/// is set on every byte except the last. Encodings longer than 10 bytes or that overflow a `u64`
+/// are rejected with [SpError::InvalidBytes].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarU64(pub u64);
+
+/// A signed integer encoded as a zigzag LEB128
+///
+/// The value is mapped to a [VarU64] so that small negative numbers stay small
+/// (0 => 0, -1 => 1, 1 => 2, -2 => 3, ...).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarI64(pub i64);
+
+impl From<u64> for VarU64 {
+    fn from(v: u64) -> Self {
+        Self(v)
+    }
+}
+impl From<VarU64> for u64 {
+    fn from(v: VarU64) -> Self {
+        v.0
+    }
+}
+impl From<i64> for VarI64 {
+    fn from(v: i64) -> Self {
+        Self(v)
+    }
+}
+impl From<VarI64> for i64 {
+    fn from(v: VarI64) -> Self {
+        v.0
+    }
+}
+
+impl VarI64 {
+    pub(crate) fn zigzag(self) -> VarU64 {
+        VarU64(((self.0 << 1) ^ (self.0 >> 63)) as u64)
+    }
+
+    pub(crate) fn unzigzag(v: VarU64) -> Self {
+        Self(((v.0 >> 1) as i64) ^ -((v.0 & 1) as i64))
+    }
+}
+
+impl VarU64 {
+    /// Encodes the value into `buf` and returns the used bytes
+    pub(crate) fn encode(self, buf: &mut [u8; MAX_VARINT_LEN]) -> &[u8] {
+        let mut v = self.0;
+        let mut len = 0;
+        loop {
+            let b = (v & 0x7F) as u8;
+            v >>= 7;
+            if v == 0 {
+                buf[len] = b;
+                len += 1;
+                break;
+            }
+            buf[len] = b | 0x80;
+            len += 1;
+        }
+        &buf[..len]
+    }
+}
+
+/// Accumulates the bytes of a LEB128 encoded u64
+#[derive(Default)]
+pub(crate) struct VarDecoder {
+    value: u64,
+    shift: u32,
+}
+
+impl VarDecoder {
+    /// Adds the next byte and returns the value once the last byte was pushed
+    pub(crate) fn push(&mut self, b: u8) -> Result<Option<VarU64>, SpError> {
+        let bits = (b & 0x7F) as u64;
+        // The 10th byte can only hold the most significant bit
+        if self.shift == 63 && bits > 1 {
+            return Err(SpError::InvalidBytes);
+        }
+        self.value |= bits << self.shift;
+
+        if b & 0x80 == 0 {
+            // A trailing zero byte only pads the value, reject it so each value has a single encoding
+            if b == 0 && self.shift > 0 {
+                return Err(SpError::InvalidBytes);
+            }
+            return Ok(Some(VarU64(self.value)));
+        }
+
+        self.shift += 7;
+        if self.shift > 63 {
+            return Err(SpError::InvalidBytes);
+        }
+        Ok(None)
+    }
+}
+
+/// Integer primitives that can be annotated with `#[sp(varint)]`
+#[doc(hidden)]
+pub trait SpVarInt: Sized {
+    /// The wire representation
+    type Var;
+    /// Converts the parsed varint, failing if it does not fit in Self
+    fn from_var(v: Self::Var) -> Result<Self, SpError>;
+    /// Converts Self into its wire representation
+    fn to_var(&self) -> Self::Var;
+}
+
+macro_rules! var_int {
+    ($var:ident, $wide:ty, $($typ:ty),+) => {
+        $(
+        impl SpVarInt for $typ {
+            type Var = $var;
+            fn from_var(v: $var) -> Result<Self, SpError> {
+                <$typ>::try_from(v.0).map_err(|_| SpError::InvalidBytes)
+            }
+            fn to_var(&self) -> $var {
+                $var(*self as $wide)
+            }
+        }
+        )+
+    };
+}
+
+var_int!(VarU64, u64, u8, u16, u32, u64, usize);
+var_int!(VarI64, i64, i8, i16, i32, i64, isize);
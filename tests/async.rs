@@ -0,0 +1,310 @@
+#![cfg(feature = "tokio")]
+
+use simple_parse::*;
+use std::{collections::BTreeMap, io::Cursor, mem::MaybeUninit};
+
+#[derive(Debug, PartialEq, SpRead, SpWrite, SpAsyncRead, SpAsyncWrite)]
+#[sp(magic = b"HD")]
+struct Header {
+    #[sp(const = 2)]
+    version: u8,
+    magic: u16,
+    #[sp(endian = "big")]
+    size: u32,
+    name_len: u8,
+    #[sp(len = "name_len")]
+    name: String,
+    attrs: BTreeMap<u8, Vec<u16>>,
+    checksum: Option<u32>,
+    #[sp(varint)]
+    seq: i64,
+    #[sp(checksum = "crc16", covers = "magic..=seq")]
+    crc: u16,
+}
+
+#[derive(Debug, PartialEq, SpRead, SpWrite, SpAsyncRead, SpAsyncWrite)]
+#[sp(magic = 0x4D53_u16)]
+enum Message {
+    Ping,
+    #[sp(id = 5)]
+    Chat(String),
+    Header(Header),
+    Key {
+        private: Vec<u8>,
+        public: Vec<u8>,
+        extra_size: u16,
+        #[sp(byte_len = "extra_size")]
+        extra: Vec<String>,
+    },
+    Peer {
+        #[sp(pad_before = 3, strict_pad)]
+        addr: std::net::SocketAddr,
+        #[sp(align = 8, pad = 0xEE)]
+        seen: std::time::SystemTime,
+        timeout: std::time::Duration,
+        flag: char,
+    },
+    Flags {
+        #[sp(bits = 3)]
+        kind: u8,
+        #[sp(bits = 1)]
+        urgent: bool,
+        #[sp(bits = 6)]
+        level: i8,
+        #[sp(calc = "data.len() + 1")]
+        len: u8,
+        #[sp(len = "len - 1")]
+        data: Vec<u8>,
+    },
+    Line {
+        #[sp(until = "\r\n")]
+        text: String,
+        #[sp(until = "0")]
+        codes: Vec<u16>,
+        #[sp(cond = "codes.len() > 1")]
+        last: Option<u16>,
+    },
+}
+
+fn sample() -> Vec<Message> {
+    vec![
+        Message::Ping,
+        Message::Chat(String::from("hello")),
+        Message::Header(Header {
+            version: 2,
+            magic: 0xBEEF,
+            size: 0x01020304,
+            name_len: 4,
+            name: String::from("name"),
+            attrs: vec![(1, vec![1, 2, 3]), (7, vec![])].into_iter().collect(),
+            checksum: Some(0x01020304),
+            seq: -1000,
+            crc: 0xE982,
+        }),
+        Message::Key {
+            private: vec![1, 2],
+            public: vec![3, 4, 5],
+            extra_size: 11,
+            extra: vec![String::from("ab"), String::from("c")],
+        },
+        Message::Peer {
+            addr: "10.0.0.1:8080".parse().unwrap(),
+            seen: std::time::UNIX_EPOCH + std::time::Duration::from_millis(1500),
+            timeout: std::time::Duration::from_secs(30),
+            flag: 'ß',
+        },
+        Message::Flags {
+            kind: 5,
+            urgent: true,
+            level: -20,
+            len: 2,
+            data: vec![0xAB],
+        },
+        Message::Line {
+            text: String::from("hello"),
+            codes: vec![200, 404],
+            last: Some(404),
+        },
+    ]
+}
+
+#[tokio::test]
+async fn async_matches_sync() {
+    for msg in sample() {
+        // Bytes produced asynchronously must be identical
+        let mut sync_bytes = Vec::new();
+        let sync_len = msg.to_writer(&mut sync_bytes).unwrap();
+        let mut async_bytes = Vec::new();
+        let async_len = msg.to_async_writer(&mut async_bytes).await.unwrap();
+        assert_eq!(sync_len, async_len);
+        assert_eq!(sync_bytes, async_bytes);
+
+        // And parse back into the same value
+        let mut ctx = SpCtx::default();
+        let v = Message::inner_from_async_reader(&mut async_bytes.as_slice(), &mut ctx)
+            .await
+            .unwrap();
+        assert_eq!(
+            ctx.cursor,
+            async_bytes.len(),
+            "ctx.cursor was not advanced properly while reading"
+        );
+        let mut tmp = MaybeUninit::uninit();
+        let expected = Message::from_reader(&mut Cursor::new(&sync_bytes), &mut tmp).unwrap();
+        assert_eq!(&v, expected);
+    }
+
+    // Truncated input must fail
+    let mut bytes = Vec::new();
+    Message::Chat(String::from("hello"))
+        .to_async_writer(&mut bytes)
+        .await
+        .unwrap();
+    bytes.pop();
+    assert!(matches!(
+        Message::from_async_reader(&mut bytes.as_slice())
+            .await
+            .unwrap_err()
+            .kind(),
+        SpError::ReadFailed(_)
+    ));
+
+    // Unknown variant
+    assert!(matches!(
+        Message::from_async_reader(&mut [0x53, 0x4D, 200u8].as_slice()).await,
+        Err(SpError::UnknownEnumVariant)
+    ));
+}
+
+#[tokio::test]
+async fn async_over_stream() {
+    let (mut client, mut server) = tokio::io::duplex(16);
+
+    // The generated futures can be moved to another task
+    let receiver = tokio::spawn(async move {
+        let mut msgs = Vec::new();
+        for _ in 0..sample().len() {
+            msgs.push(Message::from_async_reader(&mut server).await.unwrap());
+        }
+        msgs
+    });
+
+    for msg in sample() {
+        msg.to_async_writer(&mut client).await.unwrap();
+    }
+
+    assert_eq!(receiver.await.unwrap(), sample());
+}
+
+#[cfg(feature = "futures-io")]
+#[tokio::test]
+async fn async_futures_io() {
+    for msg in sample() {
+        let mut dst = FuturesIo(Vec::new());
+        msg.to_async_writer(&mut dst).await.unwrap();
+
+        let mut src = FuturesIo(dst.0.as_slice());
+        assert_eq!(Message::from_async_reader(&mut src).await.unwrap(), msg);
+    }
+}
+
+async fn read_scaled<R: SpAsyncReader + ?Sized>(
+    scale: &u8,
+    src: &mut R,
+    ctx: &mut SpCtx,
+) -> Result<u32, SpError> {
+    let v = u16::inner_from_async_reader(src, ctx).await?;
+    Ok(v as u32 * *scale as u32)
+}
+
+async fn write_scaled<W: SpAsyncWriter + ?Sized>(
+    this: &u32,
+    scale: &u8,
+    ctx: &mut SpCtx,
+    dst: &mut W,
+) -> Result<usize, SpError> {
+    ((*this / *scale as u32) as u16)
+        .inner_to_async_writer(ctx, dst)
+        .await
+}
+
+fn validate_scale(this: &u8, _ctx: &mut SpCtx) -> Result<(), SpError> {
+    if *this == 0 {
+        return Err(SpError::InvalidBytes);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn async_custom_reader() {
+    #[derive(Debug, PartialEq, SpAsyncRead, SpAsyncWrite)]
+    struct Custom(
+        #[sp(validate = "validate_scale")] u8,
+        #[sp(
+            async_reader = "read_scaled, field_0",
+            async_writer = "write_scaled, field_0"
+        )]
+        u32,
+    );
+
+    let bytes: &[u8] = &[4, 3, 0];
+    let v = Custom::from_async_reader(&mut &bytes[..]).await.unwrap();
+    assert_eq!(v, Custom(4, 12));
+
+    let mut dst = Vec::new();
+    v.to_async_writer(&mut dst).await.unwrap();
+    assert_eq!(&dst, bytes);
+
+    assert!(matches!(
+        Custom::from_async_reader(&mut &[0u8, 3, 0][..])
+            .await
+            .unwrap_err()
+            .kind(),
+        SpError::InvalidBytes
+    ));
+}
+
+#[tokio::test]
+async fn async_recursive() {
+    #[derive(Debug, PartialEq, SpAsyncRead, SpAsyncWrite)]
+    enum Expr {
+        Lit(u32),
+        Add(Box<Expr>, Box<Expr>),
+    }
+
+    let orig = Expr::Add(Box::new(Expr::Lit(1)), Box::new(Expr::Lit(2)));
+    let mut bytes = Vec::new();
+    orig.to_async_writer(&mut bytes).await.unwrap();
+    assert_eq!(
+        Expr::from_async_reader(&mut bytes.as_slice())
+            .await
+            .unwrap(),
+        orig
+    );
+}
+
+#[tokio::test]
+async fn async_arrays() {
+    let orig = [[1u32, 2], [3, 4]];
+    let mut dst = Vec::new();
+    assert_eq!(orig.to_async_writer(&mut dst).await.unwrap(), 16);
+    assert_eq!(dst, [1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0]);
+    assert_eq!(
+        <[[u32; 2]; 2]>::from_async_reader(&mut dst.as_slice())
+            .await
+            .unwrap(),
+        orig
+    );
+}
+
+#[tokio::test]
+async fn async_eof() {
+    #[derive(Debug, PartialEq, SpAsyncRead, SpAsyncWrite)]
+    struct Datagram {
+        port: u16,
+        #[sp(eof)]
+        payload: Vec<u8>,
+    }
+
+    let orig = Datagram {
+        port: 53,
+        payload: (0..10_000).map(|i| i as u8).collect(),
+    };
+    let (mut client, mut server) = tokio::io::duplex(64);
+    let receiver =
+        tokio::spawn(async move { Datagram::from_async_reader(&mut server).await.unwrap() });
+
+    // The payload ends once the stream is closed
+    orig.to_async_writer(&mut client).await.unwrap();
+    drop(client);
+    assert_eq!(receiver.await.unwrap(), orig);
+
+    #[cfg(feature = "futures-io")]
+    {
+        let mut dst = FuturesIo(Vec::new());
+        orig.to_async_writer(&mut dst).await.unwrap();
+        assert_eq!(dst.0.len(), 2 + orig.payload.len());
+        let mut src = FuturesIo(dst.0.as_slice());
+        assert_eq!(Datagram::from_async_reader(&mut src).await.unwrap(), orig);
+    }
+}
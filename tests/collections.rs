@@ -1,4 +1,4 @@
-use std::{io::Cursor, mem::MaybeUninit};
+use std::{io::Cursor, mem::MaybeUninit, num::NonZeroU8};
 
 use simple_parse::*;
 
@@ -16,3 +16,276 @@ fn collections() {
         s
     );
 }
+
+#[test]
+fn collections_raw() {
+    use std::ffi::CStr;
+
+    let mut orig = 5u32.to_le_bytes().to_vec();
+    orig.extend(b"Hello");
+    orig.extend(b"World\0");
+    orig.extend(3u32.to_le_bytes());
+    orig.extend(&[1, 2, 3]);
+
+    let mut src = orig.as_slice();
+    let mut tmp = MaybeUninit::uninit();
+    let s: &str = <&str>::from_slice(&mut src, &mut tmp).unwrap();
+    assert_eq!(s, "Hello");
+    // The returned str points into the input bytes
+    assert_eq!(s.as_ptr(), orig[4..].as_ptr());
+
+    let mut tmp = MaybeUninit::uninit();
+    let s: &CStr = <&CStr>::from_slice(&mut src, &mut tmp).unwrap();
+    assert_eq!(s.to_bytes(), b"World");
+
+    let mut tmp = MaybeUninit::uninit();
+    let s: &[u8] = <&[u8]>::from_slice(&mut src, &mut tmp).unwrap();
+    assert_eq!(s, &[1, 2, 3]);
+    assert!(src.is_empty());
+
+    // Not enough bytes
+    let mut src = &orig[..8];
+    let mut tmp = MaybeUninit::uninit();
+    assert!(matches!(
+        <&str>::from_slice(&mut src, &mut tmp),
+        Err(SpError::NotEnoughSpace)
+    ));
+
+    // Owned collections of borrowed items
+    let mut orig = 2u32.to_le_bytes().to_vec();
+    orig.extend(2u32.to_le_bytes());
+    orig.extend(b"Hi");
+    orig.extend(0u32.to_le_bytes());
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Vec<&str>>::from_slice(&mut orig.as_slice(), &mut tmp).unwrap();
+    assert_eq!(v, &["Hi", ""]);
+}
+
+#[test]
+fn collections_raw_alignment() {
+    // Force a known alignment for the u32 items
+    #[repr(C, align(4))]
+    struct Aligned([u8; 16]);
+    let mut buf = Aligned([0; 16]);
+    buf.0[..4].copy_from_slice(&3u32.to_le_bytes());
+    buf.0[4..8].copy_from_slice(&1u32.to_ne_bytes());
+    buf.0[8..12].copy_from_slice(&2u32.to_ne_bytes());
+    buf.0[12..16].copy_from_slice(&3u32.to_ne_bytes());
+
+    let mut ctx = SpCtx {
+        is_little_endian: cfg!(target_endian = "little"),
+        ..Default::default()
+    };
+    let mut tmp = MaybeUninit::uninit();
+    let v: &[u32] = <&[u32]>::inner_from_slice(&mut buf.0.as_slice(), &mut ctx, &mut tmp).unwrap();
+    assert_eq!(v, &[1, 2, 3]);
+    assert_eq!(ctx.cursor, 16);
+
+    // Items that dont start on a 4 byte boundary cannot be borrowed
+    let mut ctx = SpCtx {
+        len: Some(1),
+        ..Default::default()
+    };
+    assert!(matches!(
+        <&[u32]>::inner_from_slice(&mut &buf.0[1..], &mut ctx, &mut tmp),
+        Err(SpError::BadAlignment)
+    ));
+
+    // The owned version works at any alignment
+    let mut ctx = SpCtx {
+        len: Some(1),
+        ..Default::default()
+    };
+    let mut tmp = MaybeUninit::uninit();
+    assert!(<Vec<u32>>::inner_from_slice(&mut &buf.0[1..], &mut ctx, &mut tmp).is_ok());
+}
+
+/// Counts how many reads were issued
+struct CountingReader<'a> {
+    src: &'a [u8],
+    reads: usize,
+}
+impl std::io::Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads += 1;
+        self.src.read(buf)
+    }
+}
+
+#[test]
+fn collections_tuples() {
+    let orig = (0x0102u16, String::from("Hi"), true);
+    let mut bytes = Vec::new();
+    assert_eq!(orig.to_writer(&mut bytes).unwrap(), 2 + 4 + 2 + 1);
+    assert_eq!(&bytes, &[2, 1, 2, 0, 0, 0, b'H', b'i', 1]);
+
+    let mut tmp = MaybeUninit::uninit();
+    let v = <(u16, String, bool)>::from_reader(&mut Cursor::new(&bytes), &mut tmp).unwrap();
+    assert_eq!(v, &orig);
+
+    // Tuples of plain numbers are read at once
+    let bytes: &[u8] = &[1, 0x02, 0x01, 3, 0, 0, 0, 0, 0, 0, 0];
+    let mut src = CountingReader {
+        src: bytes,
+        reads: 0,
+    };
+    let mut ctx = SpCtx {
+        is_little_endian: false,
+        ..Default::default()
+    };
+    let mut tmp = MaybeUninit::uninit();
+    let v = <(u8, u16, u64)>::inner_from_reader(&mut src, &mut ctx, &mut tmp).unwrap();
+    assert_eq!(v, &(1, 0x0201, 0x0300000000000000));
+    assert_eq!(src.reads, 1);
+    assert_eq!(ctx.cursor, bytes.len());
+
+    // Element contents are still validated
+    let mut tmp = MaybeUninit::uninit();
+    assert!(<(u8, NonZeroU8)>::from_reader(&mut Cursor::new(&[1, 0]), &mut tmp).is_err());
+
+    // 12 elements
+    type Big = (u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, Vec<u8>);
+    let orig: Big = (0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, vec![11]);
+    let mut bytes = Vec::new();
+    orig.to_writer(&mut bytes).unwrap();
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        <Big>::from_reader(&mut Cursor::new(&bytes), &mut tmp).unwrap(),
+        &orig
+    );
+
+    // Borrowed from a slice
+    let bytes: &[u8] = &[1, 2, 0, 0, 0, b'H', b'i'];
+    let mut tmp = MaybeUninit::uninit();
+    let v = <(u8, &str)>::from_slice(&mut &bytes[..], &mut tmp).unwrap();
+    assert_eq!(v, &(1, "Hi"));
+}
+
+#[test]
+fn collections_pointers() {
+    use std::{borrow::Cow, rc::Rc, sync::Arc};
+
+    let mut bytes = 3u32.to_le_bytes().to_vec();
+    bytes.extend(b"abc");
+
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Arc<[u8]>>::from_reader(&mut Cursor::new(&bytes), &mut tmp).unwrap();
+    assert_eq!(&v[..], b"abc");
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Box<[u8]>>::from_reader(&mut Cursor::new(&bytes), &mut tmp).unwrap();
+    assert_eq!(&v[..], b"abc");
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Arc<str>>::from_reader(&mut Cursor::new(&bytes), &mut tmp).unwrap();
+    assert_eq!(&v[..], "abc");
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Box<str>>::from_reader(&mut Cursor::new(&bytes), &mut tmp).unwrap();
+    assert_eq!(&v[..], "abc");
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Cow<str>>::from_reader(&mut Cursor::new(&bytes), &mut tmp).unwrap();
+    assert!(matches!(v, Cow::Owned(s) if s == "abc"));
+
+    // Written like the underlying type
+    let mut dst = Vec::new();
+    Arc::<str>::from("abc").to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+    let mut dst = Vec::new();
+    Cow::Borrowed(&b"abc"[..]).to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+
+    // Slices follow the `len` of the context
+    let mut ctx = SpCtx {
+        len: Some(2),
+        ..Default::default()
+    };
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Box<[u16]>>::inner_from_reader(&mut Cursor::new(&[1, 0, 2, 0]), &mut ctx, &mut tmp)
+        .unwrap();
+    assert_eq!(&v[..], &[1, 2]);
+
+    let mut tmp = MaybeUninit::uninit();
+    let v =
+        <(Box<u32>, Rc<u8>)>::from_reader(&mut Cursor::new(&[1, 0, 0, 0, 2]), &mut tmp).unwrap();
+    assert_eq!((*v.0, *v.1), (1, 2));
+
+    // Cow borrows from slices
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Cow<str>>::from_slice(&mut bytes.as_slice(), &mut tmp).unwrap();
+    assert!(matches!(v, Cow::Borrowed("abc")));
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Arc<[u8]>>::from_slice(&mut bytes.as_slice(), &mut tmp).unwrap();
+    assert_eq!(&v[..], b"abc");
+}
+
+#[test]
+fn collections_zero_sized() {
+    use std::{marker::PhantomData, rc::Rc};
+
+    // Only the count is on the wire
+    let bytes: &[u8] = &[2, 0, 0, 0];
+    let mut dst = Vec::new();
+    vec![(), ()].to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        *<Vec<()>>::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap(),
+        vec![(), ()]
+    );
+    assert_eq!(
+        *<Vec<()>>::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        vec![(), ()]
+    );
+
+    let mut tmp = MaybeUninit::uninit();
+    let v = <Vec<PhantomData<u32>>>::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap();
+    assert_eq!(v.len(), 2);
+    let mut dst = Vec::new();
+    v.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        <Box<[()]>>::from_reader(&mut Cursor::new(bytes), &mut tmp)
+            .unwrap()
+            .len(),
+        2
+    );
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        <Rc<[()]>>::from_reader(&mut Cursor::new(bytes), &mut tmp)
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn collections_arrays() {
+    // No count is written before arrays
+    let orig: [u16; 3] = [0x0102, 0x0304, 0x0506];
+    let mut dst = Vec::new();
+    assert_eq!(orig.to_writer(&mut dst).unwrap(), 6);
+    assert_eq!(dst, [2, 1, 4, 3, 6, 5]);
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        *<[u16; 3]>::from_reader(&mut Cursor::new(&dst), &mut tmp).unwrap(),
+        orig
+    );
+    assert_eq!(
+        *<[u16; 3]>::from_slice(&mut dst.as_slice(), &mut tmp).unwrap(),
+        orig
+    );
+
+    // Each item keeps its own count
+    let orig = [String::from("ab"), String::from("c")];
+    let mut dst = Vec::new();
+    assert_eq!(orig.to_writer(&mut dst).unwrap(), 4 + 2 + 4 + 1);
+    assert_eq!(
+        *<[String; 2]>::from_reader(&mut Cursor::new(&dst), &mut MaybeUninit::uninit()).unwrap(),
+        orig
+    );
+
+    let mut dst = Vec::new();
+    assert_eq!(<[u8; 0]>::default().to_writer(&mut dst).unwrap(), 0);
+    assert!(dst.is_empty());
+}
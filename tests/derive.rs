@@ -158,3 +158,1410 @@ fn derive_var_size_custom_len() {
     assert_eq!(&dst, bytes);
     assert_eq!(ctx.cursor, 4 + 2 + 5, "ctx.cursor was not advanced properly while writing");
 }
+
+fn read_scaled<R: SpReader + ?Sized>(
+    scale: &u8,
+    src: &mut R,
+    ctx: &mut SpCtx,
+) -> Result<u32, SpError> {
+    let mut tmp = MaybeUninit::uninit();
+    let v = *u16::inner_from_reader(src, ctx, &mut tmp)?;
+    Ok(v as u32 * *scale as u32)
+}
+
+fn write_scaled<W: SpWriter + ?Sized>(
+    this: &u32,
+    scale: &u8,
+    ctx: &mut SpCtx,
+    dst: &mut W,
+) -> Result<usize, SpError> {
+    ((*this / *scale as u32) as u16).inner_to_writer(ctx, dst)
+}
+
+fn validate_magic(this: &u16, next: Option<&u32>, ctx: &mut SpCtx) -> Result<(), SpError> {
+    // Fields after the current one are only available when writing
+    assert_eq!(next.is_none(), ctx.is_reading);
+    if *this != 0xBEEF {
+        return Err(SpError::InvalidBytes);
+    }
+    Ok(())
+}
+
+#[test]
+fn derive_custom_reader_validator() {
+    let bytes: &[u8] = &[0xEF, 0xBE, 4, 3, 0];
+
+    #[derive(Debug, SpRead, SpWrite, PartialEq)]
+    struct Custom {
+        #[sp(validate = "validate_magic, value")]
+        magic: u16,
+        scale: u8,
+        #[sp(reader = "read_scaled, scale", writer = "write_scaled, scale")]
+        value: u32,
+    }
+
+    // Invalid magic must be caught while reading
+    let mut tmp = MaybeUninit::uninit();
+    assert!(matches!(
+        Custom::from_reader(&mut Cursor::new(&[0xAD, 0xDE, 4, 3, 0]), &mut tmp)
+            .unwrap_err()
+            .kind(),
+        SpError::InvalidBytes
+    ));
+
+    let mut ctx = SpCtx::default();
+    let v = Custom::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap();
+    assert_eq!(
+        v,
+        &mut Custom {
+            magic: 0xBEEF,
+            scale: 4,
+            value: 12,
+        }
+    );
+    assert_eq!(
+        ctx.cursor,
+        2 + 1 + 2,
+        "ctx.cursor was not advanced properly while reading"
+    );
+
+    // Convert the struct back to bytes
+    ctx.cursor = 0;
+    let mut dst = Vec::new();
+    v.inner_to_writer(&mut ctx, &mut dst).unwrap();
+    assert_eq!(&dst, bytes);
+
+    // The validator also runs before writing
+    v.magic = 0;
+    assert!(matches!(
+        v.to_writer(&mut Vec::new()).unwrap_err().kind(),
+        SpError::InvalidBytes
+    ));
+
+    #[derive(Debug, SpRead, SpWrite, PartialEq)]
+    enum CustomEnum {
+        Tuple(#[sp(validate = "validate_magic, field_1")] u16, u32),
+        Named {
+            scale: u8,
+            #[sp(reader = "read_scaled, scale", writer = "write_scaled, scale")]
+            value: u32,
+        },
+    }
+
+    let bytes: &[u8] = &[0, 0xEF, 0xBE, 1, 0, 0, 0];
+    let mut tmp = MaybeUninit::uninit();
+    let v = CustomEnum::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap();
+    assert_eq!(v, &mut CustomEnum::Tuple(0xBEEF, 1));
+    let mut dst = Vec::new();
+    v.to_writer(&mut dst).unwrap();
+    assert_eq!(&dst, bytes);
+    assert!(matches!(
+        CustomEnum::from_reader(&mut Cursor::new(&[0, 0, 0, 1, 0, 0, 0]), &mut tmp)
+            .unwrap_err()
+            .kind(),
+        SpError::InvalidBytes
+    ));
+
+    let bytes: &[u8] = &[1, 2, 5, 0];
+    let v = CustomEnum::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap();
+    assert_eq!(
+        v,
+        &mut CustomEnum::Named {
+            scale: 2,
+            value: 10
+        }
+    );
+    let mut dst = Vec::new();
+    v.to_writer(&mut dst).unwrap();
+    assert_eq!(&dst, bytes);
+}
+
+#[test]
+fn derive_raw_borrowed() {
+    let bytes: &[u8] = &[
+        1, 2, 0, 0, 0, b'H', b'i', 3, b'a', b'b', b'c', 0x01, 0xEF, 0xBE,
+    ];
+
+    #[derive(Debug, SpReadRaw, SpWrite, PartialEq)]
+    struct Borrowed<'a> {
+        v1: u8,
+        name: &'a str,
+        count: u8,
+        #[sp(len = "count")]
+        payload: &'a [u8],
+        inner: RawEnum,
+    }
+
+    #[derive(Debug, SpReadRaw, SpWrite, PartialEq)]
+    enum RawEnum {
+        #[sp(id = 1)]
+        Magic(u16),
+    }
+
+    // Try to parse with 1 byte missing
+    let mut tmp = MaybeUninit::uninit();
+    assert!(Borrowed::from_slice(&mut &bytes[..bytes.len() - 1], &mut tmp).is_err());
+
+    let mut ctx = SpCtx::default();
+    let mut src = bytes;
+    let v = Borrowed::inner_from_slice(&mut src, &mut ctx, &mut tmp).unwrap();
+    assert_eq!(
+        v,
+        &mut Borrowed {
+            v1: 1,
+            name: "Hi",
+            count: 3,
+            payload: b"abc",
+            inner: RawEnum::Magic(0xBEEF),
+        }
+    );
+    assert_eq!(
+        ctx.cursor,
+        bytes.len(),
+        "ctx.cursor was not advanced properly while reading"
+    );
+    assert!(src.is_empty());
+
+    // Convert the struct back to bytes
+    let mut dst = Vec::new();
+    v.to_writer(&mut dst).unwrap();
+    assert_eq!(&dst, bytes);
+}
+
+fn validate_compression(this: &u16, _ctx: &mut SpCtx) -> Result<(), SpError> {
+    if *this > 3 {
+        return Err(SpError::InvalidBytes);
+    }
+    Ok(())
+}
+
+#[test]
+fn derive_error_context() {
+    #[derive(Debug, SpRead, SpWrite)]
+    struct Dib {
+        width: u16,
+        #[sp(validate = "validate_compression")]
+        compression: u16,
+    }
+    #[derive(Debug, SpRead, SpWrite)]
+    enum Chunk {
+        Header(u8, Dib),
+    }
+    #[derive(Debug, SpRead, SpWrite)]
+    struct BmpHeader {
+        size: u32,
+        chunk: Chunk,
+    }
+
+    let bytes: &[u8] = &[8, 0, 0, 0, 0, 1, 2, 0, 0xAD, 0xDE];
+    let mut tmp = MaybeUninit::uninit();
+    let e = BmpHeader::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap_err();
+    match &e {
+        SpError::Context(c) => {
+            assert_eq!(c.path(), "BmpHeader.chunk.Header.1.compression");
+            assert_eq!(c.type_name, "u16");
+            assert_eq!(c.offset, bytes.len());
+            assert!(matches!(c.error, SpError::InvalidBytes));
+        }
+        _ => panic!("Missing error context : {:?}", e),
+    }
+    assert!(e
+        .to_string()
+        .contains("BmpHeader.chunk.Header.1.compression"));
+
+    // Matching on the error itself no longer sees the underlying error, match on `kind()` instead
+    assert!(!matches!(
+        BmpHeader::from_reader(&mut Cursor::new(bytes), &mut tmp),
+        Err(SpError::InvalidBytes)
+    ));
+    match BmpHeader::from_reader(&mut Cursor::new(bytes), &mut tmp) {
+        Err(e) if matches!(e.kind(), SpError::InvalidBytes) => {}
+        r => panic!("Unexpected result {:?}", r),
+    }
+
+    // Errors while writing are also tagged
+    let v = BmpHeader {
+        size: 0,
+        chunk: Chunk::Header(
+            0,
+            Dib {
+                width: 0,
+                compression: 4,
+            },
+        ),
+    };
+    match v.to_writer(&mut Vec::new()).unwrap_err() {
+        SpError::Context(c) => assert_eq!(c.path(), "BmpHeader.chunk.Header.1.compression"),
+        e => panic!("Missing error context : {:?}", e),
+    }
+}
+
+fn read_decimal<R: SpReader + ?Sized>(src: &mut R, ctx: &mut SpCtx) -> Result<u32, SpError> {
+    let mut tmp = MaybeUninit::uninit();
+    let digits = <[u8; 4]>::inner_from_reader(src, ctx, &mut tmp)?;
+    let s = std::str::from_utf8(digits).map_err(SpError::custom)?;
+    s.parse().map_err(SpError::custom)
+}
+
+fn validate_even(this: &u32, _ctx: &mut SpCtx) -> Result<(), SpError> {
+    if this % 2 != 0 {
+        return Err(SpError::msg(format!("{} is not even", this)));
+    }
+    Ok(())
+}
+
+#[test]
+fn derive_custom_error() {
+    use std::error::Error;
+
+    #[derive(Debug, SpRead)]
+    struct Decimal {
+        #[sp(reader = "read_decimal", validate = "validate_even")]
+        value: u32,
+    }
+
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        Decimal::from_reader(&mut Cursor::new(b"0042"), &mut tmp)
+            .unwrap()
+            .value,
+        42
+    );
+
+    // Errors from other crates are passed through
+    let e = Decimal::from_reader(&mut Cursor::new(b"00x2"), &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::Custom(_)));
+    let source = e.kind().source().unwrap();
+    assert!(source.downcast_ref::<std::num::ParseIntError>().is_some());
+
+    // Messages
+    let e = Decimal::from_reader(&mut Cursor::new(b"0043"), &mut tmp).unwrap_err();
+    assert_eq!(e.kind().to_string(), "43 is not even");
+
+    // io::Error can be converted with `?`
+    fn read_io() -> Result<u8, SpError> {
+        let mut b = [0u8];
+        std::io::Read::read_exact(&mut Cursor::new(&[]), &mut b)?;
+        Ok(b[0])
+    }
+    assert!(matches!(read_io(), Err(SpError::ReadFailed(_))));
+}
+
+#[test]
+fn derive_recursive() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    enum Expr {
+        Lit(u32),
+        Add(Box<Expr>, Box<Expr>),
+    }
+
+    let orig = Expr::Add(
+        Box::new(Expr::Lit(1)),
+        Box::new(Expr::Add(Box::new(Expr::Lit(2)), Box::new(Expr::Lit(3)))),
+    );
+    let mut bytes = Vec::new();
+    orig.to_writer(&mut bytes).unwrap();
+    assert_eq!(&bytes, &[1, 0, 1, 0, 0, 0, 1, 0, 2, 0, 0, 0, 0, 3, 0, 0, 0]);
+
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        Expr::from_reader(&mut Cursor::new(&bytes), &mut tmp).unwrap(),
+        &orig
+    );
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        Expr::from_slice(&mut bytes.as_slice(), &mut tmp).unwrap(),
+        &orig
+    );
+}
+
+#[test]
+fn derive_len_type() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    #[sp(len_type = "u16")]
+    struct Record {
+        name: String,
+        #[sp(len_type = "u8")]
+        tags: Vec<String>,
+        #[sp(endian = "big")]
+        data: Vec<u8>,
+        // Inherits the container's len_type
+        inner: Inner,
+    }
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Inner {
+        v: Vec<u8>,
+    }
+
+    let bytes: &[u8] = &[
+        2, 0, b'h', b'i', // Nested collections use the field's len_type
+        2, 1, b'a', 0, 0, 2, 1, 2, 1, 0, 9,
+    ];
+    let expected = Record {
+        name: String::from("hi"),
+        tags: vec![String::from("a"), String::new()],
+        data: vec![1, 2],
+        inner: Inner { v: vec![9] },
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    let v = Record::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap();
+    assert_eq!(*v, expected);
+    assert_eq!(ctx.cursor, bytes.len());
+    // The previous len_type is restored once the fields are read
+    assert_eq!(ctx.len_type, SpLenType::U32);
+    assert_eq!(
+        *Record::inner_from_slice(&mut &bytes[..], &mut SpCtx::default(), &mut tmp).unwrap(),
+        expected
+    );
+
+    let mut dst = Vec::new();
+    expected.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+
+    // Counts that dont fit in the prefix cannot be written
+    let mut too_long = expected;
+    too_long.tags = vec![String::new(); 256];
+    let e = too_long.to_writer(&mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::CountFieldOverflow));
+}
+
+#[test]
+fn derive_varint() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Packet {
+        #[sp(varint)]
+        id: u32,
+        #[sp(varint)]
+        delta: i16,
+        #[sp(varint)]
+        count: u8,
+        #[sp(len = "count")]
+        values: Vec<u8>,
+        #[sp(len_type = "varint")]
+        names: Vec<String>,
+    }
+
+    let bytes: &[u8] = &[0xAC, 0x02, 0x03, 2, 7, 8, 1, 2, b'h', b'i'];
+    let expected = Packet {
+        id: 300,
+        delta: -2,
+        count: 2,
+        values: vec![7, 8],
+        names: vec![String::from("hi")],
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Packet::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    assert_eq!(
+        *Packet::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+
+    let mut dst = Vec::new();
+    assert_eq!(expected.to_writer(&mut dst).unwrap(), bytes.len());
+    assert_eq!(dst, bytes);
+
+    // Values that dont fit in the field are invalid
+    let bytes: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x10, 0, 0, 0];
+    let e = Packet::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::InvalidBytes));
+}
+
+#[test]
+fn derive_bits() {
+    // Start of an IPv4 header
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    #[sp(endian = "big")]
+    struct Ipv4Header {
+        #[sp(bits = 4)]
+        version: u8,
+        #[sp(bits = 4)]
+        ihl: u8,
+        #[sp(bits = 6)]
+        dscp: u8,
+        #[sp(bits = 2)]
+        ecn: u8,
+        total_len: u16,
+        id: u16,
+        #[sp(bits = 1)]
+        reserved: bool,
+        #[sp(bits = 1)]
+        dont_fragment: bool,
+        #[sp(bits = 1)]
+        more_fragments: bool,
+        // Spans two bytes
+        #[sp(bits = 13)]
+        frag_offset: u16,
+        ttl: u8,
+    }
+
+    let bytes: &[u8] = &[0x45, 0x03, 0x00, 0x54, 0xAB, 0xCD, 0x41, 0x23, 64];
+    let expected = Ipv4Header {
+        version: 4,
+        ihl: 5,
+        dscp: 0,
+        ecn: 3,
+        total_len: 0x54,
+        id: 0xABCD,
+        reserved: false,
+        dont_fragment: true,
+        more_fragments: false,
+        frag_offset: 0x123,
+        ttl: 64,
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Ipv4Header::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    assert_eq!(
+        *Ipv4Header::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+
+    let mut ctx = SpCtx::default();
+    let mut dst = Vec::new();
+    assert_eq!(
+        expected.inner_to_writer(&mut ctx, &mut dst).unwrap(),
+        bytes.len()
+    );
+    assert_eq!(dst, bytes);
+    assert_eq!(ctx.cursor, bytes.len());
+
+    // Values wider than their bits are rejected
+    let mut too_wide = expected;
+    too_wide.version = 16;
+    let e = too_wide.to_writer(&mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::BitFieldOverflow));
+
+    // LSB first with unused bits at the end of the group
+    #[derive(Debug, PartialEq, SpRead, SpWrite)]
+    #[sp(bit_order = "lsb")]
+    enum Flags {
+        Packed {
+            #[sp(bits = 4)]
+            low: u8,
+            #[sp(bits = 8)]
+            mid: u8,
+            #[sp(bits = 3)]
+            signed: i8,
+            last: u8,
+        },
+    }
+    let bytes: &[u8] = &[0, 0xBF, 0x5A, 0x77];
+    let expected = Flags::Packed {
+        low: 0xF,
+        mid: 0xAB,
+        signed: -3,
+        last: 0x77,
+    };
+    assert_eq!(
+        *Flags::from_reader(&mut Cursor::new(bytes), &mut MaybeUninit::uninit()).unwrap(),
+        expected
+    );
+    let mut dst = Vec::new();
+    expected.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+
+    let too_wide = Flags::Packed {
+        low: 0,
+        mid: 0,
+        signed: 4,
+        last: 0,
+    };
+    let e = too_wide.to_writer(&mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::BitFieldOverflow));
+}
+
+#[test]
+fn derive_fixed_len() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Entry {
+        #[sp(fixed_len = 8)]
+        name: String,
+        #[sp(fixed_len = 4, pad = 0x20)]
+        mode: std::ffi::CString,
+        #[sp(fixed_len = 3)]
+        raw: Vec<u8>,
+        after: u8,
+    }
+
+    let bytes: &[u8] = &[
+        b'a', b'.', b't', b'x', b't', 0, 0, 0, b'7', b'5', b'5', b' ', 1, 2, 0, 0xFF,
+    ];
+    let expected = Entry {
+        name: String::from("a.txt"),
+        mode: std::ffi::CString::new("755").unwrap(),
+        raw: vec![1, 2],
+        after: 0xFF,
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Entry::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    assert_eq!(
+        *Entry::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+
+    let mut dst = Vec::new();
+    assert_eq!(expected.to_writer(&mut dst).unwrap(), bytes.len());
+    assert_eq!(dst, bytes);
+
+    // Values that dont fit in their slot
+    let mut too_long = expected;
+    too_long.name = String::from("too_long.txt");
+    match too_long.to_writer(&mut Vec::new()).unwrap_err().kind() {
+        SpError::FixedLenOverflow { max, len } => {
+            assert_eq!(*max, 8);
+            assert_eq!(*len, 12);
+        }
+        e => panic!("Unexpected error {:?}", e),
+    }
+
+    // Values ending with their padding would not read back the same
+    let padded = |name: &str, mode: &str, raw: Vec<u8>| Entry {
+        name: String::from(name),
+        mode: std::ffi::CString::new(mode).unwrap(),
+        raw,
+        after: 0,
+    };
+    for v in [
+        padded("ab", "75 ", vec![]),
+        padded("ab", "", vec![1, 0]),
+        padded("ab\0", "", vec![]),
+    ] {
+        let e = v.to_writer(&mut Vec::new()).unwrap_err();
+        assert!(matches!(e.kind(), SpError::AmbiguousPadding));
+    }
+    // Padding bytes within the value are kept
+    let v = padded(" a b", "7 5", vec![0, 1]);
+    let mut dst = Vec::new();
+    v.to_writer(&mut dst).unwrap();
+    assert_eq!(Entry::from_slice(&mut &dst[..], &mut tmp).unwrap(), &v);
+
+    // Padding in the middle of a CString is invalid
+    let mut invalid = bytes.to_vec();
+    invalid[8..12].copy_from_slice(&[b'7', 0, b'5', b' ']);
+    let e = Entry::from_reader(&mut Cursor::new(&invalid), &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::InvalidBytes));
+}
+
+#[test]
+fn derive_byte_len() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Record {
+        tag: u8,
+        #[sp(len_type = "u8")]
+        value: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Section {
+        size: u16,
+        #[sp(byte_len = "size")]
+        records: Vec<Record>,
+        after: u8,
+    }
+
+    let bytes: &[u8] = &[7, 0, 1, 2, 0xAA, 0xBB, 2, 1, 0xCC, 0xFF];
+    let expected = Section {
+        size: 7,
+        records: vec![
+            Record {
+                tag: 1,
+                value: vec![0xAA, 0xBB],
+            },
+            Record {
+                tag: 2,
+                value: vec![0xCC],
+            },
+        ],
+        after: 0xFF,
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Section::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    assert_eq!(
+        *Section::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+
+    // The size is computed from the records when writing
+    let mut dst = Vec::new();
+    let mut stale = expected;
+    stale.size = 0;
+    assert_eq!(stale.to_writer(&mut dst).unwrap(), bytes.len());
+    assert_eq!(dst, bytes);
+
+    // The last record goes past the end of the section
+    let mut overrun = bytes.to_vec();
+    overrun[0] = 6;
+    let e = Section::from_reader(&mut Cursor::new(&overrun), &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::InvalidBytes));
+    let e = Section::from_slice(&mut &overrun[..], &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::InvalidBytes));
+
+    // Huge sizes are errors instead of overflowing the cursor
+    #[derive(Debug, SpRead)]
+    struct Huge {
+        size: u64,
+        #[sp(byte_len = "size")]
+        _data: Vec<u8>,
+    }
+    let mut huge = u64::MAX.to_le_bytes().to_vec();
+    huge.extend_from_slice(&[1, 2, 3]);
+    let e = Huge::from_reader(&mut Cursor::new(&huge), &mut MaybeUninit::uninit()).unwrap_err();
+    assert!(matches!(
+        e.kind(),
+        SpError::InvalidBytes | SpError::LimitExceeded { .. }
+    ));
+
+    // Limits also apply to the records
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_count: 1,
+        ..Default::default()
+    });
+    let e = Section::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap_err();
+    assert!(matches!(
+        e.kind(),
+        SpError::LimitExceeded {
+            limit: SpLimit::Count,
+            ..
+        }
+    ));
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_bytes: 4,
+        ..Default::default()
+    });
+    let e = Section::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap_err();
+    assert!(matches!(
+        e.kind(),
+        SpError::LimitExceeded {
+            limit: SpLimit::Bytes,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn derive_len_expr() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Packet {
+        #[sp(calc = "payload.len() + 4")]
+        total_len: u16,
+        flags: u8,
+        #[sp(len = "(flags >> 4) + 1")]
+        ids: Vec<u8>,
+        #[sp(len = "total_len - 4")]
+        payload: Vec<u8>,
+    }
+
+    let bytes: &[u8] = &[7, 0, 0x10, 9, 8, 1, 2, 3];
+    let expected = Packet {
+        total_len: 7,
+        flags: 0x10,
+        ids: vec![9, 8],
+        payload: vec![1, 2, 3],
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Packet::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    assert_eq!(
+        *Packet::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+
+    // `total_len` is computed from the payload when writing
+    let mut dst = Vec::new();
+    let mut stale = expected;
+    stale.total_len = 0;
+    assert_eq!(stale.to_writer(&mut dst).unwrap(), bytes.len());
+    assert_eq!(dst, bytes);
+
+    // Underflow while reading
+    let mut invalid = bytes.to_vec();
+    invalid[0] = 2;
+    let e = Packet::from_reader(&mut Cursor::new(&invalid), &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::InvalidBytes));
+
+    // `flags` does not match the number of ids
+    stale.ids.push(7);
+    match stale.to_writer(&mut Vec::new()).unwrap_err().kind() {
+        SpError::LenMismatch { expected, len } => {
+            assert_eq!(*expected, 2);
+            assert_eq!(*len, 3);
+        }
+        e => panic!("Unexpected error {:?}", e),
+    }
+
+    // `total_len` is too small for the payload
+    stale.ids.pop();
+    stale.payload = vec![0; u16::MAX as usize];
+    let e = stale.to_writer(&mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::CountFieldOverflow));
+}
+
+#[test]
+fn derive_eof() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Datagram {
+        port: u16,
+        #[sp(eof)]
+        payload: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    enum Trailer {
+        Empty,
+        Text(u8, #[sp(eof)] String),
+    }
+
+    // Bigger than the first chunk that is read
+    let payload: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+    let mut bytes = vec![0x35, 0];
+    bytes.extend_from_slice(&payload);
+    let expected = Datagram {
+        port: 0x35,
+        payload,
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Datagram::inner_from_reader(&mut Cursor::new(&bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    let mut src = &bytes[..];
+    assert_eq!(*Datagram::from_slice(&mut src, &mut tmp).unwrap(), expected);
+    assert!(src.is_empty());
+
+    // No count is written before the payload
+    let mut dst = Vec::new();
+    assert_eq!(expected.to_writer(&mut dst).unwrap(), bytes.len());
+    assert_eq!(dst, bytes);
+
+    // The rest of the input can be empty
+    let empty = Datagram {
+        port: 1,
+        payload: Vec::new(),
+    };
+    assert_eq!(
+        *Datagram::from_reader(&mut Cursor::new([1u8, 0]), &mut tmp).unwrap(),
+        empty
+    );
+
+    let bytes: &[u8] = &[1, 7, b'h', b'i'];
+    let expected = Trailer::Text(7, String::from("hi"));
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        *Trailer::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(
+        *Trailer::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+    let mut dst = Vec::new();
+    expected.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+
+    // Limits still apply to the rest of the input
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_bytes: 3,
+        ..Default::default()
+    });
+    let e = Trailer::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::LimitExceeded { .. }));
+}
+
+#[test]
+fn derive_until() {
+    #[derive(Debug, Default, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Record {
+        id: u8,
+        value: u16,
+    }
+
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Request {
+        #[sp(until = "\r\n")]
+        line: String,
+        #[sp(until = "0xFF")]
+        ids: Vec<u8>,
+        #[sp(until = "Record::default()")]
+        records: Vec<Record>,
+        #[sp(until = "\n", keep_until)]
+        raw: String,
+        after: u8,
+    }
+
+    let bytes: &[u8] = &[
+        b'G', b'E', b'T', b' ', b'/', b'\r', b'\n', 1, 2, 0xFF, 5, 1, 0, 0, 0, 0, b'o', b'k',
+        b'\n', 9,
+    ];
+    let expected = Request {
+        line: String::from("GET /"),
+        ids: vec![1, 2],
+        records: vec![Record { id: 5, value: 1 }],
+        raw: String::from("ok\n"),
+        after: 9,
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Request::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    assert_eq!(
+        *Request::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+
+    let mut dst = Vec::new();
+    assert_eq!(expected.to_writer(&mut dst).unwrap(), bytes.len());
+    assert_eq!(dst, bytes);
+
+    // A kept terminator is added when missing
+    let mut req = expected;
+    req.raw = String::from("ok");
+    let mut dst = Vec::new();
+    req.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+
+    // Values that contain their terminator
+    req.line = String::from("GET /\r\nHost");
+    let e = req.to_writer(&mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::AmbiguousTerminator));
+    req.line = String::from("GET /");
+    req.ids.push(0xFF);
+    let e = req.to_writer(&mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::AmbiguousTerminator));
+    req.ids.pop();
+    req.raw = String::from("o\nk\n");
+    let e = req.to_writer(&mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::AmbiguousTerminator));
+
+    // Missing terminator
+    let e = Request::from_slice(&mut &bytes[..5], &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::NotEnoughSpace));
+    let e = Request::from_reader(&mut Cursor::new(&bytes[..9]), &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::ReadFailed(_)));
+}
+
+#[test]
+fn derive_cond() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Header {
+        #[sp(bits = 4)]
+        version: u8,
+        #[sp(bits = 4)]
+        flags: u8,
+        #[sp(cond = "flags & 0x1 != 0")]
+        checksum: Option<u32>,
+        #[sp(cond = "version >= 2")]
+        ext: Option<Vec<u8>>,
+        after: u8,
+    }
+
+    let bytes: &[u8] = &[0x21, 4, 3, 2, 1, 1, 0, 0, 0, 7, 0xFF];
+    let expected = Header {
+        version: 2,
+        flags: 1,
+        checksum: Some(0x01020304),
+        ext: Some(vec![7]),
+        after: 0xFF,
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Header::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    assert_eq!(
+        *Header::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+    let mut dst = Vec::new();
+    assert_eq!(expected.to_writer(&mut dst).unwrap(), bytes.len());
+    assert_eq!(dst, bytes);
+
+    // Nothing is read when the conditions are false
+    let bytes: &[u8] = &[0x10, 0xFF];
+    let expected = Header {
+        version: 1,
+        flags: 0,
+        checksum: None,
+        ext: None,
+        after: 0xFF,
+    };
+    assert_eq!(
+        *Header::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(
+        *Header::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+    let mut dst = Vec::new();
+    expected.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+
+    // The flag and the field disagree
+    let mut mismatch = expected;
+    mismatch.flags = 1;
+    let e = mismatch.to_writer(&mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::CondMismatch));
+    mismatch.flags = 0;
+    mismatch.ext = Some(Vec::new());
+    let e = mismatch.to_writer(&mut Vec::new()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::CondMismatch));
+}
+
+#[test]
+fn derive_magic() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    #[sp(magic = b"BM")]
+    struct Bmp {
+        #[sp(const = 0x0102, endian = "big")]
+        version: u16,
+        #[sp(const = b"\0\0")]
+        reserved: [u8; 2],
+        size: u32,
+    }
+
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    #[sp(magic = 0xCAFE_u16, id_type = "u8")]
+    enum Chunk {
+        Empty,
+        Data(#[sp(const = 7i16)] i16, u8),
+    }
+
+    let bytes: &[u8] = &[b'B', b'M', 1, 2, 0, 0, 4, 3, 2, 1];
+    let expected = Bmp {
+        version: 0x0102,
+        reserved: [0; 2],
+        size: 0x01020304,
+    };
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Bmp::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    assert_eq!(
+        *Bmp::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+    let mut dst = Vec::new();
+    assert_eq!(expected.to_writer(&mut dst).unwrap(), bytes.len());
+    assert_eq!(dst, bytes);
+
+    // Constants are written whatever the fields hold
+    let mut dst = Vec::new();
+    Bmp {
+        version: 0,
+        reserved: [9; 2],
+        size: 0x01020304,
+    }
+    .to_writer(&mut dst)
+    .unwrap();
+    assert_eq!(dst, bytes);
+
+    // The error reports both sides
+    let e = Bmp::from_slice(&mut &b"BN\x01\x02\0\0\0\0\0\0"[..], &mut tmp).unwrap_err();
+    match e.kind() {
+        SpError::BadMagic { expected, found } => {
+            assert_eq!(expected, b"BM");
+            assert_eq!(found, b"BN");
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+    let e = Bmp::from_reader(&mut Cursor::new(b"BM\x01\x02\0\x01\0\0\0\0"), &mut tmp).unwrap_err();
+    assert!(e.to_string().contains("Bmp.reserved"));
+    assert_eq!(
+        e.kind().to_string(),
+        "Expected the bytes [00, 00] but found [00, 01]"
+    );
+
+    // Enum magics come before the variant id
+    let bytes: &[u8] = &[0xFE, 0xCA, 1, 7, 0, 9];
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        *Chunk::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap(),
+        Chunk::Data(7, 9)
+    );
+    assert_eq!(
+        *Chunk::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        Chunk::Data(7, 9)
+    );
+    let mut dst = Vec::new();
+    Chunk::Data(7, 9).to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+    let mut dst = Vec::new();
+    Chunk::Empty.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, [0xFE, 0xCA, 0]);
+    let e = Chunk::from_slice(&mut &[0xCA, 0xFE, 0][..], &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::BadMagic { .. }));
+    let e = Chunk::from_slice(&mut &[0xFE, 0xCA, 1, 8, 0, 9][..], &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::BadMagic { .. }));
+}
+
+#[test]
+fn derive_padding() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Entry {
+        kind: u8,
+        #[sp(align = 4)]
+        offset: u32,
+        #[sp(pad_before = 2, pad_after = 1, pad = 0xFF)]
+        flags: u8,
+        #[sp(align = 8, strict_pad)]
+        id: u16,
+    }
+
+    let bytes: &[u8] = &[
+        7, 0, 0, 0, 4, 3, 2, 1, 0xFF, 0xFF, 9, 0xFF, 0, 0, 0, 0, 2, 1,
+    ];
+    let expected = Entry {
+        kind: 7,
+        offset: 0x01020304,
+        flags: 9,
+        id: 0x0102,
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert_eq!(
+        *Entry::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(ctx.cursor, bytes.len());
+    assert_eq!(
+        *Entry::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+    let mut dst = Vec::new();
+    assert_eq!(expected.to_writer(&mut dst).unwrap(), bytes.len());
+    assert_eq!(dst, bytes);
+
+    // Only strict padding is checked
+    let mut lax = bytes.to_vec();
+    lax[1] = 0xAA;
+    lax[9] = 0;
+    assert_eq!(
+        *Entry::from_slice(&mut &lax[..], &mut tmp).unwrap(),
+        expected
+    );
+    lax[13] = 0xAA;
+    let e = Entry::from_reader(&mut Cursor::new(&lax), &mut tmp).unwrap_err();
+    assert!(matches!(
+        e.kind(),
+        SpError::BadPadding {
+            expected: 0,
+            found: 0xAA
+        }
+    ));
+    let e = Entry::from_slice(&mut &lax[..], &mut tmp).unwrap_err();
+    assert!(matches!(
+        e.kind(),
+        SpError::BadPadding {
+            expected: 0,
+            found: 0xAA
+        }
+    ));
+
+    // Alignment follows the position in the whole input
+    #[derive(Debug, PartialEq, SpRead, SpWrite)]
+    struct Outer {
+        tag: u16,
+        entry: Entry,
+    }
+    let mut dst = Vec::new();
+    Outer {
+        tag: 1,
+        entry: expected,
+    }
+    .to_writer(&mut dst)
+    .unwrap();
+    assert_eq!(dst.len(), 2 + 1 + 1 + 4 + 4 + 4 + 2);
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        Outer::from_reader(&mut Cursor::new(&dst), &mut tmp)
+            .unwrap()
+            .entry
+            .id,
+        0x0102
+    );
+}
+
+#[test]
+#[rustfmt::skip]
+fn derive_offset() {
+    #[derive(Debug, PartialEq, SpSeekRead, SpSeekWrite)]
+    struct Entry {
+        name_offset: u8,
+        #[sp(offset = "name_offset", offset_from = "struct", restore)]
+        name: [u8; 2],
+        value: u16,
+    }
+
+    #[derive(Debug, PartialEq, SpSeekRead, SpSeekWrite)]
+    struct File {
+        data_offset: u32,
+        entry: Entry,
+        #[sp(offset = "data_offset", restore)]
+        data: [u8; 3],
+        trailer: u16,
+        tail_offset: u8,
+        #[sp(offset = "tail_offset")]
+        tail: u8,
+    }
+
+    let bytes: &[u8] = &[
+        16, 0, 0, 0,
+        9, 2, 1,
+        4, 3,
+        20,
+        0, 0, 0,
+        b'a', b'b',
+        0,
+        7, 8, 9,
+        0,
+        0xAA,
+    ];
+    let expected = File {
+        data_offset: 16,
+        entry: Entry {
+            name_offset: 9,
+            name: *b"ab",
+            value: 0x0102,
+        },
+        data: [7, 8, 9],
+        trailer: 0x0304,
+        tail_offset: 20,
+        tail: 0xAA,
+    };
+
+    let mut src = Cursor::new(bytes);
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(*File::from_seek_reader(&mut src, &mut tmp).unwrap(), expected);
+    assert_eq!(src.position(), bytes.len() as u64);
+
+    let mut dst = Cursor::new(Vec::new());
+    expected.to_seek_writer(&mut dst).unwrap();
+    assert_eq!(dst.get_ref(), bytes);
+
+    // Offsets are relative to where reading started
+    let mut shifted = vec![0xFF; 5];
+    shifted.extend_from_slice(bytes);
+    let mut src = Cursor::new(&shifted);
+    src.set_position(5);
+    assert_eq!(*File::from_seek_reader(&mut src, &mut tmp).unwrap(), expected);
+
+    let mut bad = bytes.to_vec();
+    bad[0] = 0xFF;
+    let e = File::from_seek_reader(&mut Cursor::new(&bad), &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::ReadFailed(_)));
+
+    // The start of the outer type is restored when a nested type fails to be written
+    let entry = Entry {
+        name_offset: 200,
+        name: *b"ab",
+        value: 0x0102,
+    };
+    let mut buf = [0u8; 8];
+    let mut dst = Cursor::new(&mut buf[..]);
+    dst.set_position(1);
+    let mut ctx = SpCtx {
+        cursor: 1,
+        ..Default::default()
+    };
+    let e = entry.inner_to_seek_writer(&mut ctx, &mut dst).unwrap_err();
+    assert!(matches!(e.kind(), SpError::NotEnoughSpace));
+    assert_eq!(ctx.struct_start, 0);
+}
+
+/// Adler-32, to make sure that other algorithms can be plugged in
+#[derive(Default)]
+struct Adler32 {
+    a: u32,
+    b: u32,
+}
+impl SpChecksum for Adler32 {
+    type Output = u32;
+    fn update(&mut self, bytes: &[u8]) {
+        for v in bytes {
+            self.a = (self.a + *v as u32) % 65521;
+            self.b = (self.b + self.a) % 65521;
+        }
+    }
+    fn finish(&self) -> u32 {
+        (self.b << 16) | (self.a + 1)
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn derive_checksum() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Check {
+        data: [u8; 9],
+        #[sp(checksum = "crc32")]
+        crc32: u32,
+        #[sp(checksum = "crc16", covers = "data")]
+        crc16: u16,
+        #[sp(checksum = "sum8", covers = "data..crc32")]
+        sum: u8,
+        #[sp(checksum = "Adler32", covers = "data..=sum", endian = "big")]
+        adler: u32,
+    }
+
+    // Known check values over "123456789"
+    let mut bytes = b"123456789".to_vec();
+    bytes.extend_from_slice(&0xCBF43926u32.to_le_bytes());
+    bytes.extend_from_slice(&0xBB3Du16.to_le_bytes());
+    bytes.push(0xDD);
+    let mut adler = Adler32::default();
+    adler.update(&bytes);
+    bytes.extend_from_slice(&adler.finish().to_be_bytes());
+    let expected = Check {
+        data: *b"123456789",
+        crc32: 0xCBF43926,
+        crc16: 0xBB3D,
+        sum: 0xDD,
+        adler: adler.finish(),
+    };
+
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(*Check::from_reader(&mut Cursor::new(&bytes), &mut tmp).unwrap(), expected);
+    assert_eq!(*Check::from_slice(&mut &bytes[..], &mut tmp).unwrap(), expected);
+
+    // The checksums are computed when writing
+    let mut dst = Vec::new();
+    Check { data: *b"123456789", crc32: 0, crc16: 0, sum: 0, adler: 0 }.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+
+    let mut bad = bytes.clone();
+    bad[0] = b'0';
+    let e = Check::from_slice(&mut &bad[..], &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::BadChecksum { expected: _, found: 0xCBF43926 }));
+    bad[0] = b'1';
+    bad[15] = 0;
+    let e = Check::from_reader(&mut Cursor::new(&bad), &mut tmp).unwrap_err();
+    assert!(matches!(e.kind(), SpError::BadChecksum { expected: 0xDD, found: 0 }));
+
+    // Only the covered fields are part of the checksum, including their bit fields and padding
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    struct Frame {
+        len: u8,
+        #[sp(bits = 4)]
+        kind: u8,
+        #[sp(bits = 4)]
+        flags: u8,
+        #[sp(len = "len", pad_before = 1)]
+        payload: Vec<u8>,
+        #[sp(checksum = "sum8", covers = "kind..=payload")]
+        sum: u8,
+        trailer: u8,
+    }
+    let bytes: &[u8] = &[2, 0x12, 0, 3, 4, 0x12 + 3 + 4, 0xFF];
+    let expected = Frame { len: 2, kind: 1, flags: 2, payload: vec![3, 4], sum: 0x19, trailer: 0xFF };
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(*Frame::from_slice(&mut &bytes[..], &mut tmp).unwrap(), expected);
+    assert_eq!(*Frame::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap(), expected);
+    let mut dst = Vec::new();
+    expected.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+}
+
+#[test]
+fn derive_enum_ids() {
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    enum Chunk {
+        #[sp(id = b"IHDR")]
+        Header { width: u32, height: u32 },
+        #[sp(id = b"IEND")]
+        End,
+    }
+
+    #[derive(Debug, PartialEq, SpRead, SpReadRaw, SpWrite)]
+    #[sp(endian = "little")]
+    enum Delta {
+        #[sp(id = -2)]
+        Back,
+        Stay,
+        Forward(u8),
+        #[sp(id = 300)]
+        Jump(i16),
+    }
+
+    let bytes: &[u8] = b"IHDR\x02\0\0\0\x01\0\0\0";
+    let expected = Chunk::Header {
+        width: 2,
+        height: 1,
+    };
+    let mut tmp = MaybeUninit::uninit();
+    assert_eq!(
+        *Chunk::from_reader(&mut Cursor::new(bytes), &mut tmp).unwrap(),
+        expected
+    );
+    assert_eq!(
+        *Chunk::from_slice(&mut &bytes[..], &mut tmp).unwrap(),
+        expected
+    );
+    let mut dst = Vec::new();
+    expected.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, bytes);
+    let mut dst = Vec::new();
+    Chunk::End.to_writer(&mut dst).unwrap();
+    assert_eq!(dst, b"IEND");
+    assert!(matches!(
+        Chunk::from_slice(&mut &b"IDAT"[..], &mut tmp),
+        Err(SpError::UnknownEnumVariant)
+    ));
+
+    // Negative ids use the smallest signed type that fits every id (i16 here)
+    let mut tmp = MaybeUninit::uninit();
+    for (v, bytes) in [
+        (Delta::Back, &[0xFE, 0xFF][..]),
+        (Delta::Stay, &[0xFF, 0xFF]),
+        (Delta::Forward(3), &[0, 0, 3]),
+        (Delta::Jump(-1), &[0x2C, 0x01, 0xFF, 0xFF]),
+    ] {
+        let mut dst = Vec::new();
+        v.to_writer(&mut dst).unwrap();
+        assert_eq!(dst, bytes);
+        assert_eq!(*Delta::from_slice(&mut &bytes[..], &mut tmp).unwrap(), v);
+    }
+}
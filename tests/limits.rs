@@ -0,0 +1,353 @@
+use simple_parse::*;
+use std::{io::Cursor, mem::MaybeUninit};
+
+fn limit_of(e: &SpError) -> Option<SpLimit> {
+    match e.kind() {
+        SpError::LimitExceeded { limit, .. } => Some(*limit),
+        _ => None,
+    }
+}
+
+#[test]
+fn limit_bytes() {
+    // Claims to contain u32::MAX bytes
+    let mut bytes = vec![0xFF, 0xFF, 0xFF, 0xFF];
+    bytes.extend_from_slice(&[0; 64]);
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_bytes: 32,
+        ..Default::default()
+    });
+    let e = <Vec<u8>>::inner_from_reader(
+        &mut Cursor::new(&bytes),
+        &mut ctx,
+        &mut MaybeUninit::uninit(),
+    )
+    .unwrap_err();
+    match e {
+        SpError::LimitExceeded {
+            limit,
+            max,
+            requested,
+        } => {
+            assert_eq!(limit, SpLimit::Bytes);
+            assert_eq!(max, 32);
+            assert!(requested > 32);
+        }
+        e => panic!("Unexpected error {:?}", e),
+    }
+
+    // Null terminated strings are also bounded
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_bytes: 8,
+        ..Default::default()
+    });
+    let e = <std::ffi::CString>::inner_from_reader(
+        &mut Cursor::new(&[b'a'; 64]),
+        &mut ctx,
+        &mut MaybeUninit::uninit(),
+    )
+    .unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Bytes));
+    assert_eq!(ctx.cursor, 8);
+
+    // Same from a slice
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_bytes: 32,
+        ..Default::default()
+    });
+    let e = <&[u8]>::inner_from_slice(&mut bytes.as_slice(), &mut ctx, &mut MaybeUninit::uninit())
+        .unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Bytes));
+}
+
+#[test]
+fn limit_count_and_alloc() {
+    let bytes: &[u8] = &[3, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0];
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_count: 2,
+        ..Default::default()
+    });
+    let e = <Vec<u32>>::inner_from_reader(
+        &mut Cursor::new(bytes),
+        &mut ctx,
+        &mut MaybeUninit::uninit(),
+    )
+    .unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Count));
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_count: 2,
+        ..Default::default()
+    });
+    let e = <std::collections::BTreeSet<u32>>::inner_from_reader(
+        &mut Cursor::new(bytes),
+        &mut ctx,
+        &mut MaybeUninit::uninit(),
+    )
+    .unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Count));
+
+    // The allocation limit is shared by every collection
+    #[derive(Debug, SpRead)]
+    struct Pair {
+        a: Vec<u32>,
+        b: Vec<u32>,
+    }
+    let mut two = bytes.to_vec();
+    two.extend_from_slice(bytes);
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_alloc: 12,
+        ..Default::default()
+    });
+    let mut tmp = MaybeUninit::uninit();
+    let e = Pair::inner_from_reader(&mut Cursor::new(&two), &mut ctx, &mut tmp).unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Alloc));
+    match e {
+        SpError::Context(c) => assert_eq!(c.path(), "Pair.b"),
+        e => panic!("Unexpected error {:?}", e),
+    }
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_alloc: 24,
+        ..Default::default()
+    });
+    let v = Pair::inner_from_reader(&mut Cursor::new(&two), &mut ctx, &mut tmp).unwrap();
+    assert_eq!(v.b, vec![1, 2, 3]);
+    assert_eq!(ctx.allocated, 24);
+}
+
+#[test]
+fn limit_depth() {
+    #[derive(Debug, SpRead, SpReadRaw)]
+    struct Inner {
+        v: u8,
+    }
+    #[derive(Debug, SpRead, SpReadRaw)]
+    struct Middle {
+        inner: Inner,
+    }
+    #[derive(Debug, SpRead, SpReadRaw)]
+    struct Outer {
+        middle: Middle,
+    }
+
+    let bytes: &[u8] = &[1];
+    let mut tmp = MaybeUninit::uninit();
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_depth: 3,
+        ..Default::default()
+    });
+    Outer::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap();
+    assert_eq!(ctx.depth, 0);
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_depth: 2,
+        ..Default::default()
+    });
+    let e = Outer::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Depth));
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_depth: 2,
+        ..Default::default()
+    });
+    let e = Outer::inner_from_slice(&mut &bytes[..], &mut ctx, &mut tmp).unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Depth));
+}
+
+#[test]
+fn limit_restored_on_error() {
+    #[derive(Debug, SpRead, SpReadRaw)]
+    struct Inner {
+        v: u32,
+    }
+    #[derive(Debug, SpRead, SpReadRaw)]
+    struct Outer {
+        a: u8,
+        inner: Inner,
+    }
+
+    // Missing bytes in a nested type must not leave the depth incremented
+    let bytes: &[u8] = &[1, 2];
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = SpCtx::default();
+    assert!(Outer::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).is_err());
+    assert_eq!(ctx.depth, 0);
+    let mut ctx = SpCtx::default();
+    assert!(Outer::inner_from_slice(&mut &bytes[..], &mut ctx, &mut tmp).is_err());
+    assert_eq!(ctx.depth, 0);
+
+    #[derive(Debug, SpSeekRead)]
+    struct Entry {
+        offset: u8,
+        #[sp(offset = "offset", offset_from = "struct")]
+        value: u32,
+    }
+    // Entry starts after the first byte and its value is out of bounds
+    let mut src = Cursor::new([0xFF, 200]);
+    src.set_position(1);
+    let mut ctx = SpCtx {
+        cursor: 1,
+        ..Default::default()
+    };
+    assert!(Entry::inner_from_seek_reader(&mut src, &mut ctx, &mut MaybeUninit::uninit()).is_err());
+    assert_eq!(ctx.depth, 0);
+    assert_eq!(ctx.struct_start, 0);
+}
+
+#[test]
+fn limit_seek_back() {
+    // Every field reads the same 4 bytes
+    #[derive(Debug, SpSeekRead)]
+    struct Aliased {
+        a: u32,
+        #[sp(offset = "0")]
+        b: u32,
+        #[sp(offset = "0")]
+        c: u32,
+    }
+    let bytes: &[u8] = &[1, 2, 3, 4];
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_bytes: 12,
+        ..Default::default()
+    });
+    Aliased::inner_from_seek_reader(
+        &mut Cursor::new(bytes),
+        &mut ctx,
+        &mut MaybeUninit::uninit(),
+    )
+    .unwrap();
+
+    let mut ctx = SpCtx::with_limits(SpLimits {
+        max_bytes: 8,
+        ..Default::default()
+    });
+    let e = Aliased::inner_from_seek_reader(
+        &mut Cursor::new(bytes),
+        &mut ctx,
+        &mut MaybeUninit::uninit(),
+    )
+    .unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Bytes));
+}
+
+#[test]
+fn limit_count_until_eof() {
+    #[derive(Debug, SpRead, SpReadRaw)]
+    struct Lines {
+        #[sp(until = "0")]
+        ids: Vec<u8>,
+        #[sp(until = "\n")]
+        line: String,
+        #[sp(eof)]
+        rest: Vec<u8>,
+    }
+    let bytes: &[u8] = &[1, 2, 0, b'a', b'b', b'\n', 7, 8, 9];
+
+    let limits = |max_count| {
+        SpCtx::with_limits(SpLimits {
+            max_count,
+            ..Default::default()
+        })
+    };
+    let mut tmp = MaybeUninit::uninit();
+    let v = Lines::inner_from_reader(&mut Cursor::new(bytes), &mut limits(3), &mut tmp).unwrap();
+    assert_eq!(v.rest, [7, 8, 9]);
+    Lines::inner_from_slice(&mut &bytes[..], &mut limits(3), &mut tmp).unwrap();
+
+    // Items of until lists, bytes of until strings and eof fields
+    for max_count in [0, 2] {
+        let e = Lines::inner_from_reader(&mut Cursor::new(bytes), &mut limits(max_count), &mut tmp)
+            .unwrap_err();
+        assert_eq!(limit_of(&e), Some(SpLimit::Count));
+        let e =
+            Lines::inner_from_slice(&mut &bytes[..], &mut limits(max_count), &mut tmp).unwrap_err();
+        assert_eq!(limit_of(&e), Some(SpLimit::Count));
+    }
+    let mut eof_only = bytes.to_vec();
+    eof_only.push(10);
+    let e = Lines::inner_from_reader(&mut Cursor::new(&eof_only), &mut limits(3), &mut tmp)
+        .unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Count));
+    let e = Lines::inner_from_slice(&mut &eof_only[..], &mut limits(3), &mut tmp).unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Count));
+}
+
+#[test]
+fn limit_alloc_eof() {
+    #[derive(Debug, SpRead, SpReadRaw)]
+    struct Datagram {
+        port: u16,
+        #[sp(eof)]
+        payload: Vec<u8>,
+    }
+    let limits = |max_alloc| {
+        SpCtx::with_limits(SpLimits {
+            max_alloc,
+            ..Default::default()
+        })
+    };
+
+    // Only the bytes of the payload are charged, as when reading from a slice
+    let mut bytes = vec![53, 0];
+    bytes.extend_from_slice(&[0xAA; 10]);
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = limits(16);
+    let v = Datagram::inner_from_reader(&mut Cursor::new(&bytes), &mut ctx, &mut tmp).unwrap();
+    assert_eq!(v.payload, [0xAA; 10]);
+    assert_eq!(ctx.allocated, 10);
+    let mut ctx = limits(16);
+    Datagram::inner_from_slice(&mut bytes.as_slice(), &mut ctx, &mut tmp).unwrap();
+    assert_eq!(ctx.allocated, 10);
+
+    let mut ctx = limits(0);
+    let v = Datagram::inner_from_reader(&mut Cursor::new(&[53, 0]), &mut ctx, &mut tmp).unwrap();
+    assert!(v.payload.is_empty());
+
+    bytes.extend_from_slice(&[0xBB; 10]);
+    let e = Datagram::inner_from_reader(&mut Cursor::new(&bytes), &mut limits(16), &mut tmp)
+        .unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Alloc));
+    let e =
+        Datagram::inner_from_slice(&mut bytes.as_slice(), &mut limits(16), &mut tmp).unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Alloc));
+}
+
+#[test]
+fn limit_alloc_until() {
+    #[derive(Debug, SpRead, SpReadRaw)]
+    struct Line {
+        #[sp(until = "\r\n")]
+        text: String,
+        after: u8,
+    }
+    let limits = |max_alloc| {
+        SpCtx::with_limits(SpLimits {
+            max_alloc,
+            ..Default::default()
+        })
+    };
+
+    // Chunks are reserved up front but only the kept bytes stay charged
+    let bytes = b"hello\r\n\x07";
+    let mut tmp = MaybeUninit::uninit();
+    let mut ctx = limits(8);
+    let v = Line::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap();
+    assert_eq!(v.text, "hello");
+    assert_eq!(v.after, 7);
+    assert_eq!(ctx.allocated, 5);
+    let mut ctx = limits(8);
+    Line::inner_from_slice(&mut &bytes[..], &mut ctx, &mut tmp).unwrap();
+    assert_eq!(ctx.allocated, 5);
+
+    let long = b"hello world\r\n\x07";
+    let e = Line::inner_from_reader(&mut Cursor::new(long), &mut limits(8), &mut tmp).unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Alloc));
+    let e = Line::inner_from_slice(&mut &long[..], &mut limits(8), &mut tmp).unwrap_err();
+    assert_eq!(limit_of(&e), Some(SpLimit::Alloc));
+}
@@ -16,7 +16,7 @@ fn all_same_bytes(buf: &[u8]) -> bool {
         }
     }
 
-    return true;
+    true
 }
 
 macro_rules! test_primitive {
@@ -214,3 +214,282 @@ fn primitives_special() {
         <NonZeroIsize>::from_reader(&mut Cursor::new(non_zero), &mut MaybeUninit::uninit()).is_ok()
     );
 }
+
+/// A fixed size destination that does not rely on std::io
+struct FixedBuf {
+    buf: [u8; 4],
+    len: usize,
+}
+impl SpWriter for FixedBuf {
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError> {
+        if self.buf.len() - self.len < buf.len() {
+            return Err(SpError::NotEnoughSpace);
+        }
+        self.buf[self.len..self.len + buf.len()].copy_from_slice(buf);
+        self.len += buf.len();
+        Ok(())
+    }
+}
+
+#[test]
+fn primitives_custom_writer() {
+    let mut dst = FixedBuf {
+        buf: [0; 4],
+        len: 0,
+    };
+    assert_eq!(0xAABBu16.to_writer(&mut dst).unwrap(), 2);
+    assert_eq!(0xCCDDu16.to_writer(&mut dst).unwrap(), 2);
+    assert_eq!(&dst.buf, &[0xBB, 0xAA, 0xDD, 0xCC]);
+    assert!(matches!(
+        1u8.to_writer(&mut dst),
+        Err(SpError::NotEnoughSpace)
+    ));
+}
+
+/// A destination that always fails like a closed socket
+struct BrokenPipe;
+impl std::io::Write for BrokenPipe {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::ErrorKind::BrokenPipe.into())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn primitives_write_failed() {
+    // I/O errors are kept
+    match 1u32.to_writer(&mut BrokenPipe) {
+        Err(SpError::WriteFailed(e)) => assert_eq!(e.kind(), std::io::ErrorKind::BrokenPipe),
+        r => panic!("Unexpected result {:?}", r),
+    }
+    assert!(matches!(
+        vec![1u8, 2].to_writer(&mut BrokenPipe),
+        Err(SpError::WriteFailed(_))
+    ));
+
+    // Full in-memory buffers are not I/O failures
+    let mut buf = [0u8; 3];
+    assert!(matches!(
+        1u32.to_writer(&mut &mut buf[..]),
+        Err(SpError::NotEnoughSpace)
+    ));
+
+    // Custom writers keep I/O errors on the write side
+    struct Forward<W>(W);
+    impl<W: std::io::Write> SpWriter for Forward<W> {
+        fn write_bytes(&mut self, buf: &[u8]) -> Result<(), SpError> {
+            self.0.write_all(buf).map_err(SpError::from_write_error)
+        }
+    }
+    assert!(matches!(
+        1u32.to_writer(&mut Forward(BrokenPipe)),
+        Err(SpError::WriteFailed(_))
+    ));
+    assert!(matches!(
+        1u32.to_writer(&mut Forward(&mut buf[..])),
+        Err(SpError::NotEnoughSpace)
+    ));
+}
+
+#[test]
+fn primitives_zero_sized() {
+    use std::marker::PhantomData;
+
+    let mut src = Cursor::new(&[1u8]);
+    let mut ctx = SpCtx::default();
+    <()>::inner_from_reader(&mut src, &mut ctx, &mut MaybeUninit::uninit()).unwrap();
+    <PhantomData<String>>::inner_from_reader(&mut src, &mut ctx, &mut MaybeUninit::uninit())
+        .unwrap();
+    assert_eq!(ctx.cursor, 0);
+    assert_eq!(src.position(), 0);
+
+    let mut dst = Vec::new();
+    assert_eq!(().to_writer(&mut dst).unwrap(), 0);
+    assert_eq!(PhantomData::<str>.to_writer(&mut dst).unwrap(), 0);
+    assert!(dst.is_empty());
+}
+
+#[test]
+fn primitives_values() {
+    use std::net::*;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    macro_rules! round_trip {
+        ($typ:ty, $val:expr, $bytes:expr) => {{
+            let v: $typ = $val;
+            let bytes: &[u8] = &$bytes;
+            let mut dst = Vec::new();
+            assert_eq!(v.to_writer(&mut dst).unwrap(), bytes.len());
+            assert_eq!(dst, bytes, "{} wire layout", stringify!($typ));
+
+            let mut ctx = SpCtx::default();
+            let mut tmp = MaybeUninit::uninit();
+            let r = <$typ>::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp).unwrap();
+            assert_eq!(*r, v);
+            assert_eq!(ctx.cursor, bytes.len());
+
+            let mut src = bytes;
+            assert_eq!(
+                *<$typ>::inner_from_slice(
+                    &mut src,
+                    &mut SpCtx::default(),
+                    &mut MaybeUninit::uninit()
+                )
+                .unwrap(),
+                v
+            );
+            assert!(src.is_empty());
+        }};
+    }
+
+    round_trip!(char, 'é', [0xE9, 0, 0, 0]);
+    round_trip!(
+        Duration,
+        Duration::new(2, 5),
+        [2, 0, 0, 0, 0, 0, 0, 0, 5, 0, 0, 0]
+    );
+    round_trip!(
+        SystemTime,
+        UNIX_EPOCH + Duration::new(1, 2),
+        [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0]
+    );
+    // Times before the epoch round the seconds down
+    round_trip!(
+        SystemTime,
+        UNIX_EPOCH - Duration::new(1, 1),
+        [0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xC9, 0x9A, 0x3B]
+    );
+    round_trip!(Ipv4Addr, Ipv4Addr::new(192, 168, 0, 1), [192, 168, 0, 1]);
+    round_trip!(
+        Ipv6Addr,
+        Ipv6Addr::LOCALHOST,
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+    );
+    round_trip!(
+        IpAddr,
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+        [4, 10, 0, 0, 2]
+    );
+    round_trip!(
+        SocketAddr,
+        "[::1]:80".parse().unwrap(),
+        [6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 80, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    round_trip!(
+        SocketAddrV4,
+        SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 0x1F90),
+        [127, 0, 0, 1, 0x90, 0x1F]
+    );
+
+    // Invalid data
+    let invalid: &[&[u8]] = &[&[0x00, 0xD8, 0, 0], &[0, 0, 0x11, 0]];
+    for bytes in invalid {
+        let e =
+            <char>::from_reader(&mut Cursor::new(bytes), &mut MaybeUninit::uninit()).unwrap_err();
+        assert!(matches!(e.kind(), SpError::InvalidBytes));
+    }
+    let e = <Duration>::from_reader(
+        &mut Cursor::new(&[0, 0, 0, 0, 0, 0, 0, 0, 0x00, 0xCA, 0x9A, 0x3B]),
+        &mut MaybeUninit::uninit(),
+    )
+    .unwrap_err();
+    assert!(matches!(e.kind(), SpError::InvalidBytes));
+    let e =
+        <IpAddr>::from_slice(&mut &[5u8, 1, 2, 3, 4][..], &mut MaybeUninit::uninit()).unwrap_err();
+    assert!(matches!(e.kind(), SpError::InvalidBytes));
+}
+
+#[test]
+fn primitives_varint() {
+    let cases: &[(u64, &[u8])] = &[
+        (0, &[0]),
+        (127, &[0x7F]),
+        (300, &[0xAC, 0x02]),
+        (
+            u64::MAX,
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01],
+        ),
+    ];
+    for (v, bytes) in cases {
+        let mut ctx = SpCtx::default();
+        let mut dst = Vec::new();
+        assert_eq!(
+            VarU64(*v).inner_to_writer(&mut ctx, &mut dst).unwrap(),
+            bytes.len()
+        );
+        assert_eq!(&dst, bytes);
+        assert_eq!(ctx.cursor, bytes.len());
+
+        let mut ctx = SpCtx::default();
+        let mut tmp = MaybeUninit::uninit();
+        assert_eq!(
+            VarU64::inner_from_reader(&mut Cursor::new(bytes), &mut ctx, &mut tmp)
+                .unwrap()
+                .0,
+            *v
+        );
+        assert_eq!(ctx.cursor, bytes.len());
+
+        // Trailing bytes are left untouched
+        let src = [*bytes, &[0xAA]].concat();
+        let mut slice = src.as_slice();
+        assert_eq!(
+            VarU64::inner_from_slice(&mut slice, &mut SpCtx::default(), &mut tmp)
+                .unwrap()
+                .0,
+            *v
+        );
+        assert_eq!(slice, &[0xAA]);
+    }
+
+    // Zigzag encoding keeps small negative numbers small
+    let cases: &[(i64, &[u8])] = &[
+        (0, &[0]),
+        (-1, &[1]),
+        (1, &[2]),
+        (-64, &[0x7F]),
+        (64, &[0x80, 0x01]),
+    ];
+    for (v, bytes) in cases {
+        let mut dst = Vec::new();
+        VarI64(*v).to_writer(&mut dst).unwrap();
+        assert_eq!(&dst, bytes);
+        assert_eq!(
+            VarI64::from_reader(&mut Cursor::new(bytes), &mut MaybeUninit::uninit())
+                .unwrap()
+                .0,
+            *v
+        );
+    }
+    for v in [i64::MIN, i64::MAX] {
+        let mut dst = Vec::new();
+        VarI64(v).to_writer(&mut dst).unwrap();
+        assert_eq!(
+            VarI64::from_reader(&mut Cursor::new(&dst), &mut MaybeUninit::uninit())
+                .unwrap()
+                .0,
+            v
+        );
+    }
+
+    // Over-long and overflowing encodings
+    let invalid: &[&[u8]] = &[
+        &[
+            0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00,
+        ],
+        &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00],
+        &[0x80, 0x00],
+        &[0xAC, 0x82, 0x00],
+        &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02],
+    ];
+    for bytes in invalid {
+        let e =
+            VarU64::from_reader(&mut Cursor::new(bytes), &mut MaybeUninit::uninit()).unwrap_err();
+        assert!(matches!(e, SpError::InvalidBytes));
+        let e = VarU64::from_slice(&mut &bytes[..], &mut MaybeUninit::uninit()).unwrap_err();
+        assert!(matches!(e, SpError::InvalidBytes));
+    }
+    assert!(VarU64::from_slice(&mut &[0x80u8][..], &mut MaybeUninit::uninit()).is_err());
+}
@@ -0,0 +1,20 @@
+use simple_parse::*;
+
+#[derive(SpRead)]
+enum Chunk {
+    #[sp(id = b"IHDR")]
+    Header,
+    #[sp(id = b"IHDR")]
+    Other,
+}
+
+#[derive(SpWrite)]
+enum Delta {
+    #[sp(id = -2)]
+    Back,
+    Stay,
+    #[sp(id = "-1")]
+    Again,
+}
+
+fn main() {}
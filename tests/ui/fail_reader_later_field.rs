@@ -0,0 +1,19 @@
+use simple_parse::*;
+
+fn read_value<R: SpReader + ?Sized>(
+    _after: &u16,
+    src: &mut R,
+    ctx: &mut SpCtx,
+) -> Result<u32, SpError> {
+    let mut tmp = std::mem::MaybeUninit::uninit();
+    Ok(*u32::inner_from_reader(src, ctx, &mut tmp)?)
+}
+
+#[derive(SpRead)]
+struct Struct {
+    #[sp(reader = "read_value, after")]
+    value: u32,
+    after: u16,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use simple_parse::*;
+
+// Fields after the annotated field must be taken as `Option<&T>`
+fn validate_value(_this: &u32, _after: &u16, _ctx: &mut SpCtx) -> Result<(), SpError> {
+    Ok(())
+}
+
+#[derive(SpRead)]
+struct Struct {
+    #[sp(validate = "validate_value, after")]
+    value: u32,
+    after: u16,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use simple_parse::*;
+
+fn validate_value(_this: &u32, _other: &u8, _ctx: &mut SpCtx) -> Result<(), SpError> {
+    Ok(())
+}
+
+#[derive(SpRead)]
+enum Enum {
+    Named {
+        #[sp(validate = "validate_value, unknown")]
+        value: u32,
+    },
+}
+
+fn main() {}
@@ -0,0 +1,43 @@
+use simple_parse::*;
+
+fn read_value<R: SpReader + ?Sized>(
+    count: &u8,
+    flag: &bool,
+    src: &mut R,
+    ctx: &mut SpCtx,
+) -> Result<u32, SpError> {
+    let mut tmp = std::mem::MaybeUninit::uninit();
+    let v = *u32::inner_from_reader(src, ctx, &mut tmp)?;
+    Ok(if *flag { v + *count as u32 } else { v })
+}
+
+fn validate_value(
+    _this: &u32,
+    _count: &u8,
+    _after: Option<&u16>,
+    _ctx: &mut SpCtx,
+) -> Result<(), SpError> {
+    Ok(())
+}
+
+#[derive(SpRead, SpWrite)]
+struct Struct {
+    count: u8,
+    flag: bool,
+    #[sp(reader = "read_value, count, flag", validate = "validate_value, count, after")]
+    value: u32,
+    after: u16,
+}
+
+#[derive(SpRead, SpWrite)]
+enum Enum {
+    Tuple(
+        u8,
+        bool,
+        #[sp(reader = "read_value, field_0, field_1", validate = "validate_value, field_0, field_3")]
+        u32,
+        u16,
+    ),
+}
+
+fn main() {}